@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use chress::{
+    board::{r#move::Move, Board},
+    move_gen::MoveGen,
+};
+use chress_engine::{evaluation::evaluate, search::Search};
+
+/// Parses a move typed at the CLI, accepting both SAN (`Nf3`, `O-O`) and long algebraic
+/// notation (`g1f3`).
+pub fn parse_move(board: &Board, move_gen: &MoveGen, input: &str) -> Option<Move> {
+    if let Ok(r#move) = board.parse_san(input, move_gen) {
+        return Some(r#move);
+    }
+
+    if !(4..=5).contains(&input.len()) {
+        return None;
+    }
+
+    Move::try_from(input).ok()
+}
+
+/// Returns the static evaluation of the position from White's perspective.
+pub fn eval(board: &Board, move_gen: &MoveGen) -> String {
+    format!("Eval: {}", evaluate(board, move_gen))
+}
+
+/// Searches the position to a fixed depth and returns the score and principal variation,
+/// with the moves written in SAN.
+pub fn bestline(board: &Board, move_gen: &Arc<MoveGen>, depth: u8) -> String {
+    let (score, pv) = Search::fixed_depth(*board, Arc::clone(move_gen), depth);
+
+    let mut board = *board;
+    let mut line = Vec::new();
+
+    for r#move in pv {
+        line.push(board.move_to_san(r#move, move_gen));
+        board.make_move(r#move).unwrap();
+    }
+
+    format!("Score: {score}\nLine: {}", line.join(" "))
+}
+
+#[cfg(test)]
+mod commands_tests {
+    use chress::board::square::Square;
+
+    use super::*;
+
+    #[test]
+    fn parse_move_san() {
+        let move_gen = MoveGen::new();
+
+        let mut board = Board::default();
+        let r#move = parse_move(&board, &move_gen, "e4").unwrap();
+        board.make_move(r#move).unwrap();
+
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        let mut board = Board::default();
+        let r#move = parse_move(&board, &move_gen, "Nf3").unwrap();
+        board.make_move(r#move).unwrap();
+
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"
+        );
+    }
+
+    #[test]
+    fn parse_move_long_algebraic() {
+        let move_gen = MoveGen::new();
+        let board = Board::default();
+
+        assert_eq!(
+            parse_move(&board, &move_gen, "g1f3"),
+            Some(Move::new(Square::G1, Square::F3))
+        );
+        assert_eq!(parse_move(&board, &move_gen, "Nf"), None);
+    }
+
+    #[test]
+    fn eval_startpos() {
+        let output = eval(&Board::default(), &MoveGen::new());
+        let score = output.strip_prefix("Eval: ").unwrap();
+
+        assert!(score.parse::<i32>().unwrap().abs() < 50);
+    }
+
+    #[test]
+    fn bestline_startpos() {
+        let move_gen = Arc::new(MoveGen::new());
+        let output = bestline(&Board::default(), &move_gen, 3);
+
+        let mut lines = output.lines();
+        assert!(lines.next().unwrap().starts_with("Score: "));
+
+        let line = lines.next().unwrap().strip_prefix("Line: ").unwrap();
+        assert_eq!(line.split(' ').count(), 3);
+    }
+}
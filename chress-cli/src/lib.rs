@@ -1,2 +1,3 @@
+pub mod commands;
 pub mod perft;
 pub mod uci;
\ No newline at end of file
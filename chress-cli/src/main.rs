@@ -3,11 +3,12 @@ extern crate chress;
 use std::{io::stdin, process::Command, sync::Arc};
 
 use chress::{
-    board::{r#move::Move, Board, START_FEN},
+    board::{Board, START_FEN},
+    debug::{divide, divide_output},
     move_gen::MoveGen,
 };
 
-use chress_cli::{perft, uci};
+use chress_cli::{commands, perft, uci};
 
 fn main() -> std::io::Result<()> {
     let mut board = Board::default();
@@ -52,16 +53,23 @@ fn main() -> std::io::Result<()> {
 
                 "clear" | "cls" => {
                     if cfg!(windows) {
-                        Command::new("cls").spawn().expect("Failed to clear screen");
+                        Command::new("cls")
+                            .status()
+                            .expect("Failed to clear screen");
                     } else if cfg!(unix) {
                         Command::new("clear")
-                            .spawn()
+                            .status()
                             .expect("Failed to clear screen");
                     }
                 }
 
                 "undo" => {
-                    if let Err(unmake_move_error) = board.unmake_move(move_list.pop().unwrap()) {
+                    let Some(move_data) = move_list.pop() else {
+                        println!("No moves to undo");
+                        break;
+                    };
+
+                    if let Err(unmake_move_error) = board.unmake_move(move_data) {
                         println!("Error: {}", unmake_move_error);
                     }
                 }
@@ -91,6 +99,38 @@ fn main() -> std::io::Result<()> {
                     perft::perft(board, &move_gen, depth);
                 }
 
+                "divide" => {
+                    let Some(depth) = arguments.first().cloned() else {
+                        println!("Missing arguments for divide");
+                        break;
+                    };
+
+                    let Ok(depth) = depth.parse::<usize>() else {
+                        println!("Invalid argument for divide: '{}'", arguments[0]);
+                        break;
+                    };
+
+                    let (total, results) = divide(board, &move_gen, depth);
+                    print!("{}", divide_output(total, &results));
+                }
+
+                "eval" => println!("{}", commands::eval(&board, &move_gen)),
+
+                "bestline" => {
+                    let depth = match arguments.first() {
+                        Some(depth) => {
+                            let Ok(depth) = depth.parse::<u8>() else {
+                                println!("Invalid argument for bestline: '{}'", arguments[0]);
+                                break;
+                            };
+                            depth
+                        }
+                        None => 4,
+                    };
+
+                    println!("{}", commands::bestline(&board, &move_gen, depth));
+                }
+
                 "uci" => {
                     uci::uci()?;
                     break 'main;
@@ -100,10 +140,13 @@ fn main() -> std::io::Result<()> {
 
                 "move" => {
                     for potential_move in arguments {
-                        if let Ok(r#move) = Move::try_from(potential_move) {
-                            board
+                        if let Some(r#move) =
+                            commands::parse_move(&board, &move_gen, potential_move)
+                        {
+                            let move_data = board
                                 .make_move(r#move)
                                 .unwrap_or_else(|_| panic!("Illegal move '{potential_move}'"));
+                            move_list.push(move_data);
                         } else {
                             println!("Invalid move '{potential_move}'");
                             break;
@@ -72,7 +72,7 @@ pub fn perft(board: Board, move_gen: &MoveGen, depth: usize) {
         let line = line.trim();
 
         if set_total {
-            expected_total = line.split(' ').last().unwrap().parse().unwrap();
+            expected_total = line.split(' ').next_back().unwrap().parse().unwrap();
             break;
         }
 
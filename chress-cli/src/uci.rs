@@ -1,59 +1,215 @@
-use std::{io::stdin, sync::Arc};
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{stdin, stdout, BufRead, Write},
+    sync::Arc,
+};
 
 use chress::{
     board::{r#move::Move, Board},
+    debug::{divide, divide_output},
     move_gen::MoveGen,
 };
 use chress_engine::search::{MoveTime, SearchManager, SearchSettings};
 
 const UCI_STRING: &str = "id name Chress\nid author Luc de Cafmeyer\nuciok";
 
-pub fn uci() -> std::io::Result<()> {
-    let mut board = Board::default();
-    let move_gen = Arc::new(MoveGen::new());
-    let mut search_manager = SearchManager::new(Arc::clone(&move_gen));
+#[derive(Debug, PartialEq, Eq)]
+pub enum Registration {
+    Later,
+    Details {
+        name: Option<String>,
+        code: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Uci,
+    IsReady,
+    UciNewGame,
+    Register(Registration),
+    Position(Vec<String>),
+    Go(Vec<String>),
+    Stop,
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseCommandError {
+    Empty,
+    UnknownCommand(String),
+    BadArguments,
+}
+
+impl Display for ParseCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Error for ParseCommandError {}
 
-    let mut buf = String::new();
-    let mut arguments: Vec<String> = Vec::new();
+impl Registration {
+    fn parse(arguments: &[String]) -> Result<Self, ParseCommandError> {
+        if arguments.first().map(String::as_str) == Some("later") {
+            return Ok(Self::Later);
+        }
 
-    println!("{}", UCI_STRING);
+        let mut name: Option<Vec<&str>> = None;
+        let mut code: Option<Vec<&str>> = None;
+        let mut current = None;
 
-    loop {
-        stdin().read_line(&mut buf)?;
+        // Both the name and the code may contain spaces, so collect tokens until the
+        // next keyword is found
+        for argument in arguments {
+            match argument.as_str() {
+                "name" => current = Some(name.insert(Vec::new())),
+                "code" => current = Some(code.insert(Vec::new())),
+                token => match current.as_mut() {
+                    Some(tokens) => tokens.push(token),
+                    None => return Err(ParseCommandError::BadArguments),
+                },
+            }
+        }
 
-        let mut input = buf.split_ascii_whitespace().map(|i| String::from(i.trim()));
+        if name.is_none() && code.is_none() {
+            return Err(ParseCommandError::BadArguments);
+        }
+
+        Ok(Self::Details {
+            name: name.map(|tokens| tokens.join(" ")),
+            code: code.map(|tokens| tokens.join(" ")),
+        })
+    }
+}
+
+impl TryFrom<&str> for Command {
+    type Error = ParseCommandError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut input = value.split_ascii_whitespace();
 
         let Some(command) = input.next() else {
-            continue;
+            return Err(ParseCommandError::Empty);
         };
 
-        arguments.extend(input);
+        let arguments = input.map(String::from).collect::<Vec<String>>();
+
+        Ok(match command {
+            "uci" => Self::Uci,
+            "isready" => Self::IsReady,
+            "ucinewgame" => Self::UciNewGame,
+            "register" => Self::Register(Registration::parse(&arguments)?),
+            "position" => Self::Position(arguments),
+            "go" => Self::Go(arguments),
+            "stop" => Self::Stop,
+            "quit" => Self::Quit,
+            _ => return Err(ParseCommandError::UnknownCommand(command.to_owned())),
+        })
+    }
+}
+
+/// Reads the search limits from the arguments of `go`. Without any, the search is
+/// infinite and only ends on `stop`.
+fn search_settings(arguments: &[String]) -> SearchSettings {
+    let mut settings = SearchSettings::default();
+
+    for (i, arg) in arguments.iter().enumerate() {
+        match arg.as_str() {
+            "infinite" => settings.movetime = MoveTime::Infinite,
+            "movetime" => {
+                let millis = arguments
+                    .get(i + 1)
+                    .expect("Missing argument for movetime")
+                    .parse::<u32>()
+                    .expect("Invalid argument for movetime");
+                settings.movetime = MoveTime::Millis(millis);
+            }
+            "depth" => {
+                let depth = arguments
+                    .get(i + 1)
+                    .expect("Missing argument for depth")
+                    .parse::<u8>()
+                    .expect("Invalid argument for depth");
+                settings.max_depth = Some(depth);
+            }
+            "nodes" => {
+                let nodes = arguments
+                    .get(i + 1)
+                    .expect("Missing argument for nodes")
+                    .parse::<u64>()
+                    .expect("Invalid argument for nodes");
+                settings.max_nodes = Some(nodes);
+            }
+            _ => (),
+        }
+    }
+
+    settings
+}
+
+/// Reads commands from `reader`, one per line. Several commands may arrive in a single
+/// write, they are still returned one at a time and in order.
+///
+/// Unknown commands are skipped, as required by the UCI protocol.
+fn read_commands(reader: impl BufRead) -> impl Iterator<Item = std::io::Result<Command>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) => Command::try_from(line.as_str()).ok().map(Ok),
+        Err(err) => Some(Err(err)),
+    })
+}
+
+/// State of the UCI loop between commands. Searches run on their own threads, so
+/// commands like `isready` and `stop` are still answered while one is running.
+struct Uci {
+    board: Board,
+    move_gen: Arc<MoveGen>,
+    search_manager: SearchManager,
+}
 
-        match command.as_str() {
-            "quit" => break,
+impl Uci {
+    fn new() -> Self {
+        let move_gen = Arc::new(MoveGen::new());
 
-            "uci" => println!("{}", UCI_STRING),
-            "ucinewgame" => println!("readyok"),
-            "isready" => println!("readyok"),
+        Self {
+            board: Board::default(),
+            search_manager: SearchManager::new(Arc::clone(&move_gen)),
+            move_gen,
+        }
+    }
 
-            "position" => {
+    /// Runs a single command, writing any response to `output`. Returns false once the
+    /// loop should end.
+    fn execute(&mut self, command: Command, output: &mut impl Write) -> std::io::Result<bool> {
+        match command {
+            Command::Quit => return Ok(false),
+
+            Command::Uci => writeln!(output, "{}", UCI_STRING)?,
+            Command::UciNewGame => writeln!(output, "readyok")?,
+            Command::IsReady => writeln!(output, "readyok")?,
+
+            // Chress is free software, so registration is accepted and never checked
+            Command::Register(_) => (),
+
+            Command::Position(arguments) => {
                 let Some(first) = arguments.first() else {
-                    continue;
+                    return Ok(true);
                 };
 
                 let first_move_index = match first.as_str() {
                     "startpos" => {
-                        board = Board::default();
+                        self.board = Board::default();
                         1
                     }
                     "fen" => {
                         let Some(fen) = arguments.get(1..7) else {
-                            continue;
+                            return Ok(true);
                         };
 
                         let fen = fen.join(" ");
 
-                        board.load_from_fen(&fen, &move_gen).unwrap();
+                        self.board.load_from_fen(&fen, &self.move_gen).unwrap();
 
                         7
                     }
@@ -62,52 +218,177 @@ pub fn uci() -> std::io::Result<()> {
 
                 let moves = arguments
                     .get(first_move_index..)
-                    .expect("Invalid arguments for position");
+                    .expect("Invalid arguments for position")
+                    .iter()
+                    .skip_while(|&argument| argument == "moves")
+                    .map(|mv| Move::try_from(mv.as_str()).expect("Bad format for UCI move"))
+                    .collect::<Vec<Move>>();
 
-                for mv in moves {
-                    let mv = Move::try_from(mv.as_str()).expect("Bad format for UCI move");
-
-                    board.make_move(mv).unwrap();
-                }
+                self.board.make_moves(&moves).unwrap();
             }
 
-            "go" => {
-                let mut settings = SearchSettings::default();
-
-                for (i, arg) in arguments.iter().enumerate() {
-                    match arg.as_str() {
-                        "infinite" => settings.movetime = MoveTime::Infinite,
-                        "movetime" => {
-                            let millis = arguments
-                                .get(i + 1)
-                                .expect("Missing argument for movetime")
-                                .parse::<u32>()
-                                .expect("Invalid argument for movetime");
-                            settings.movetime = MoveTime::Millis(millis);
-                        }
-                        _ => (),
-                    }
+            Command::Go(arguments) => {
+                // Same output format as Stockfish, so perft results can be compared
+                if arguments.first().map(String::as_str) == Some("perft") {
+                    let Some(Ok(depth)) = arguments.get(1).map(|depth| depth.parse::<usize>())
+                    else {
+                        return Ok(true);
+                    };
+
+                    let (total, results) = divide(self.board, &self.move_gen, depth);
+                    write!(output, "{}", divide_output(total, &results))?;
+
+                    return Ok(true);
                 }
 
-                search_manager.settings = settings;
+                self.search_manager.settings = search_settings(&arguments);
 
-                search_manager.start_search(board);
+                self.search_manager.start_search(self.board);
             }
 
-            "stop" => {
-                if !search_manager.running {
-                    continue;
+            Command::Stop => {
+                if self.search_manager.running {
+                    self.search_manager.stop();
                 }
-
-                search_manager.stop();
             }
-
-            _ => (),
         }
 
-        arguments.clear();
-        buf.clear();
+        Ok(true)
+    }
+}
+
+pub fn uci() -> std::io::Result<()> {
+    let mut uci = Uci::new();
+    let mut output = stdout();
+
+    writeln!(output, "{}", UCI_STRING)?;
+
+    for command in read_commands(stdin().lock()) {
+        if !uci.execute(command?, &mut output)? {
+            break;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod uci_tests {
+    use super::*;
+
+    #[test]
+    fn several_commands_in_one_write() {
+        let input = "uci\nucinewgame\nfoo\n\nisready\r\nposition startpos moves e2e4\ngo";
+
+        let commands = read_commands(input.as_bytes())
+            .collect::<std::io::Result<Vec<Command>>>()
+            .unwrap();
+
+        assert_eq!(
+            commands,
+            [
+                Command::Uci,
+                Command::UciNewGame,
+                Command::IsReady,
+                Command::Position(vec![
+                    "startpos".to_owned(),
+                    "moves".to_owned(),
+                    "e2e4".to_owned()
+                ]),
+                Command::Go(Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn isready_during_search() {
+        let mut uci = Uci::new();
+        let mut output = Vec::new();
+
+        uci.execute(Command::Go(vec!["infinite".to_owned()]), &mut output)
+            .unwrap();
+        assert!(uci.execute(Command::IsReady, &mut output).unwrap());
+
+        assert_eq!(output, b"readyok\n");
+        assert!(uci.search_manager.running);
+        assert!(!uci
+            .search_manager
+            .cancelled
+            .lock()
+            .unwrap()
+            .load(std::sync::atomic::Ordering::Relaxed));
+
+        uci.execute(Command::Stop, &mut output).unwrap();
+        assert!(!uci.search_manager.running);
+    }
+
+    #[test]
+    fn position_with_moves() {
+        let mut uci = Uci::new();
+        let mut output = Vec::new();
+
+        let command = Command::try_from("position startpos moves e2e4 e7e5").unwrap();
+        uci.execute(command, &mut output).unwrap();
+
+        assert_eq!(
+            uci.board.fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+    }
+
+    #[test]
+    fn register_later() {
+        assert_eq!(
+            Command::try_from("register later"),
+            Ok(Command::Register(Registration::Later))
+        );
+    }
+
+    #[test]
+    fn register_name_and_code() {
+        assert_eq!(
+            Command::try_from("register name Stefan MK code 4359874324\n"),
+            Ok(Command::Register(Registration::Details {
+                name: Some("Stefan MK".to_owned()),
+                code: Some("4359874324".to_owned()),
+            }))
+        );
+    }
+
+    #[test]
+    fn register_without_arguments() {
+        assert_eq!(
+            Command::try_from("register"),
+            Err(ParseCommandError::BadArguments)
+        );
+    }
+
+    #[test]
+    fn unknown_command() {
+        assert_eq!(
+            Command::try_from("foo bar"),
+            Err(ParseCommandError::UnknownCommand("foo".to_owned()))
+        );
+        assert_eq!(Command::try_from("  \n"), Err(ParseCommandError::Empty));
+    }
+
+    #[test]
+    fn bare_go_searches_infinitely() {
+        assert_eq!(Command::try_from("go"), Ok(Command::Go(Vec::new())));
+
+        let settings = search_settings(&[]);
+
+        assert!(matches!(settings.movetime, MoveTime::Infinite));
+        assert_eq!(settings.max_depth, None);
+        assert_eq!(settings.max_nodes, None);
+    }
+
+    #[test]
+    fn go_depth() {
+        let arguments = ["depth", "3"].map(String::from);
+        let settings = search_settings(&arguments);
+
+        assert_eq!(settings.max_depth, Some(3));
+        assert!(matches!(settings.movetime, MoveTime::Infinite));
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Command, Stdio},
+};
+
+#[test]
+fn stop_after_depth_limit_prints_one_bestmove() {
+    let mut cli = Command::new(env!("CARGO_BIN_EXE_chress_cli"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = cli.stdin.take().unwrap();
+    let mut lines = BufReader::new(cli.stdout.take().unwrap())
+        .lines()
+        .map(Result::unwrap);
+
+    stdin
+        .write_all(b"uci\nposition startpos\ngo depth 1\n")
+        .unwrap();
+    stdin.flush().unwrap();
+
+    // Wait for the search to reach its depth limit
+    lines.find(|line| line.starts_with("bestmove")).unwrap();
+
+    // The search has already ended, so stopping it shouldn't print another move
+    stdin.write_all(b"stop\nisready\n").unwrap();
+    stdin.flush().unwrap();
+
+    let after_stop = lines
+        .by_ref()
+        .take_while(|line| line != "readyok")
+        .collect::<Vec<String>>();
+
+    cli.kill().unwrap();
+    cli.wait().unwrap();
+
+    assert!(
+        after_stop.iter().all(|line| !line.starts_with("bestmove")),
+        "{after_stop:?}"
+    );
+}
@@ -0,0 +1,17 @@
+use chress::{board::Board, move_gen::MoveGen};
+use chress_engine::evaluation::evaluate;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn evaluate_kiwipete(c: &mut Criterion) {
+    let move_gen = MoveGen::new();
+    let board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+    c.bench_function("evaluate_kiwipete", |b| {
+        b.iter(|| evaluate(black_box(&board), &move_gen))
+    });
+}
+
+criterion_group!(benches, evaluate_kiwipete);
+criterion_main!(benches);
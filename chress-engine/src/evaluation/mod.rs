@@ -1,6 +1,12 @@
-use chress::board::{color::Color, piece::Piece, Board};
+use std::fmt::Write;
 
-pub const PIECE_SCORES: [i32; 6] = [320, 350, 500, 900, 100, 20000];
+use chress::{
+    board::{bitboard::Bitboard, color::Color, piece::Piece, square::Square, Board},
+    move_gen::{king_attacks, MoveGen},
+};
+
+/// Indexed by `Piece`, so the king comes before the pawn.
+pub const PIECE_SCORES: [i32; 6] = [320, 350, 500, 900, 20000, 100];
 
 #[rustfmt::skip]
 pub const PIECE_SQUARE_TABLES: [[i32; 64]; 6] = [
@@ -66,7 +72,189 @@ pub const PIECE_SQUARE_TABLES: [[i32; 64]; 6] = [
     ],
 ];
 
-pub fn evaluate(board: &Board) -> i32 {
+/// `PIECE_SQUARE_TABLES` indexed by color, piece and square.
+///
+/// The tables are written as White sees the board, with a8 first, so White's table is
+/// flipped vertically to be indexed by square and Black's already is.
+pub const COLORED_PIECE_SQUARE_TABLES: [[[i32; 64]; 6]; 2] = {
+    let mut tables = [[[0; 64]; 6]; 2];
+
+    let mut piece = 0;
+    while piece < 6 {
+        let mut square = 0;
+        while square < 64 {
+            let flipped = Square::ALL[square].flip_vertical() as usize;
+
+            tables[Color::White as usize][piece][square] = PIECE_SQUARE_TABLES[piece][flipped];
+            tables[Color::Black as usize][piece][square] = PIECE_SQUARE_TABLES[piece][square];
+
+            square += 1;
+        }
+        piece += 1;
+    }
+
+    tables
+};
+
+/// Phase of the starting position.
+pub const OPENING_PHASE: i32 = 24;
+
+/// Positions at or below this phase are endgames.
+pub const ENDGAME_PHASE: i32 = 6;
+
+/// Percentage of the evaluation kept in drawish opposite-colored bishop endgames.
+const OPPOSITE_BISHOPS_SCALE: i32 = 25;
+
+/// How much non-pawn material is left, from `OPENING_PHASE` with every piece on the
+/// board down to 0 with only kings and pawns.
+pub fn phase(board: &Board) -> i32 {
+    let mut phase = 0;
+
+    for piece in Piece::ALL {
+        for color in Color::ALL {
+            phase +=
+                (board.bitboard(piece, color).0.count_ones() * piece.phase_weight() as u32) as i32;
+        }
+    }
+
+    phase.min(OPENING_PHASE)
+}
+
+/// Checks for an endgame where each side has a single bishop, on squares of opposite
+/// colors, no knights, the same heavy pieces and a difference of at most two pawns.
+/// These are often drawn even when one side is ahead.
+pub fn is_opposite_bishop_endgame(board: &Board) -> bool {
+    if phase(board) > ENDGAME_PHASE {
+        return false;
+    }
+
+    let count = |piece, color| board.bitboard(piece, color).0.count_ones();
+
+    for piece in [Piece::Rook, Piece::Queen] {
+        if count(piece, Color::White) != count(piece, Color::Black) {
+            return false;
+        }
+    }
+
+    if count(Piece::Pawn, Color::White).abs_diff(count(Piece::Pawn, Color::Black)) > 2 {
+        return false;
+    }
+
+    let knights = count(Piece::Knight, Color::White) + count(Piece::Knight, Color::Black);
+    let white_bishops = board.bitboard(Piece::Bishop, Color::White);
+    let black_bishops = board.bitboard(Piece::Bishop, Color::Black);
+
+    let (Ok(white_bishop), Ok(black_bishop)) = (
+        Square::try_from(white_bishops),
+        Square::try_from(black_bishops),
+    ) else {
+        return false;
+    };
+
+    knights == 0 && white_bishop.is_light() != black_bishop.is_light()
+}
+
+/// Penalty for each attack on a square in the king zone, indexed by the attacking `Piece`.
+const KING_ZONE_ATTACK_WEIGHTS: [i32; 6] = [8, 8, 10, 15, 0, 4];
+
+/// The king square and every square next to it.
+pub fn king_zone(square: Square) -> Bitboard {
+    king_attacks(square) | square.bitboard()
+}
+
+/// Sums the weighted attacks by the opponent on the king zone of `color`, scaled down as
+/// pieces come off the board.
+pub fn king_danger(board: &Board, move_gen: &MoveGen, color: Color) -> i32 {
+    let Ok(king_square) = Square::try_from(board.bitboard(Piece::King, color)) else {
+        return 0;
+    };
+
+    let enemy = color.inverse();
+    let occupied = board.occupied();
+    let mut zone = king_zone(king_square);
+    let mut danger = 0;
+
+    for _ in 0..zone.0.count_ones() {
+        let square = Square::ALL[zone.pop_lsb() as usize];
+        let attackers = move_gen.attackers_to(board, square, occupied);
+
+        for piece in Piece::ALL {
+            let count = (attackers & board.bitboard(piece, enemy)).0.count_ones() as i32;
+            danger += count * KING_ZONE_ATTACK_WEIGHTS[piece as usize];
+        }
+    }
+
+    danger * phase(board) / OPENING_PHASE
+}
+
+/// Penalty for a king stuck on its back rank behind its own pawns.
+pub const BACK_RANK_PENALTY: i32 = 30;
+
+/// Checks whether the king of `color` is on its back rank with every square in front of
+/// it blocked by its own pawns, while the opponent still has a rook or queen to mate with
+/// and no friendly rook or queen guards the back rank.
+pub fn is_back_rank_weak(board: &Board, color: Color) -> bool {
+    let Ok(king_square) = Square::try_from(board.bitboard(Piece::King, color)) else {
+        return false;
+    };
+
+    let enemy = color.inverse();
+    let heavy_pieces = board.bitboard(Piece::Rook, enemy) | board.bitboard(Piece::Queen, enemy);
+
+    if heavy_pieces.is_empty() {
+        return false;
+    }
+
+    let (back_rank, front_rank) = match color {
+        Color::White => (0, 1),
+        Color::Black => (7, 6),
+    };
+
+    if king_square.rank() != back_rank {
+        return false;
+    }
+
+    let guards = board.bitboard(Piece::Rook, color) | board.bitboard(Piece::Queen, color);
+
+    if !(guards & Bitboard::rank_mask(back_rank)).is_empty() {
+        return false;
+    }
+
+    let front = king_attacks(king_square) & Bitboard::rank_mask(front_rank);
+
+    (front & !board.bitboard(Piece::Pawn, color)).is_empty()
+}
+
+/// Bonus for a rook on a file without pawns.
+pub const ROOK_OPEN_FILE_BONUS: i32 = 20;
+
+/// Bonus for a rook on a file without friendly pawns.
+pub const ROOK_SEMI_OPEN_FILE_BONUS: i32 = 10;
+
+/// Sums the open and semi-open file bonuses for the rooks of `color`.
+pub fn rook_file_bonus(board: &Board, color: Color) -> i32 {
+    let friendly_pawns = board.bitboard(Piece::Pawn, color);
+    let enemy_pawns = board.bitboard(Piece::Pawn, color.inverse());
+
+    let mut rooks = board.bitboard(Piece::Rook, color);
+    let mut bonus = 0;
+
+    for _ in 0..rooks.0.count_ones() {
+        let file = Bitboard::file_mask(Square::ALL[rooks.pop_lsb() as usize].file());
+
+        if (file & friendly_pawns).is_empty() {
+            bonus += if (file & enemy_pawns).is_empty() {
+                ROOK_OPEN_FILE_BONUS
+            } else {
+                ROOK_SEMI_OPEN_FILE_BONUS
+            };
+        }
+    }
+
+    bonus
+}
+
+pub fn evaluate(board: &Board, move_gen: &MoveGen) -> i32 {
     let mut score = 0;
 
     for piece in Piece::ALL {
@@ -74,27 +262,119 @@ pub fn evaluate(board: &Board) -> i32 {
             let mut bb = board.bitboard(piece, color);
 
             for _ in 0..bb.0.count_ones() {
-                let i = bb.pop_lsb();
-
-                let pst_index = match color {
-                    Color::White => 63 - i as usize,
-                    Color::Black => i as usize,
-                };
+                let i = bb.pop_lsb() as usize;
 
-                let adjusted_score =
-                    PIECE_SCORES[piece as usize] + PIECE_SQUARE_TABLES[piece as usize][pst_index];
+                let adjusted_score = PIECE_SCORES[piece as usize]
+                    + COLORED_PIECE_SQUARE_TABLES[color as usize][piece as usize][i];
 
                 score += adjusted_score * color.direction() as i32;
             }
         }
     }
 
+    score -= king_danger(board, move_gen, Color::White);
+    score += king_danger(board, move_gen, Color::Black);
+
+    score += rook_file_bonus(board, Color::White);
+    score -= rook_file_bonus(board, Color::Black);
+
+    if is_back_rank_weak(board, Color::White) {
+        score -= BACK_RANK_PENALTY;
+    }
+    if is_back_rank_weak(board, Color::Black) {
+        score += BACK_RANK_PENALTY;
+    }
+
+    if is_opposite_bishop_endgame(board) {
+        score = score * OPPOSITE_BISHOPS_SCALE / 100;
+    }
+
     score
 }
 
+/// Bonus for being the side to move, which is usually worth a little.
+pub const TEMPO: i32 = 10;
+
+/// Evaluates the position from the perspective of the side to move, as required by negamax.
+/// Includes the `TEMPO` bonus, unlike `evaluate`.
+pub fn evaluate_relative(board: &Board, move_gen: &MoveGen) -> i32 {
+    evaluate(board, move_gen) * board.active_color.direction() as i32 + TEMPO
+}
+
+/// Renders the board alongside material counts, the phase and each evaluation term, all
+/// from White's perspective. Intended for debugging the engine.
+pub fn pretty_eval(board: &Board, move_gen: &MoveGen) -> String {
+    let mut material = 0;
+    let mut tables = 0;
+    let mut counts = String::new();
+
+    for color in Color::ALL {
+        write!(counts, "{color:?}:").unwrap();
+
+        for piece in Piece::ALL {
+            let mut bb = board.bitboard(piece, color);
+
+            write!(counts, " {}{}", bb.0.count_ones(), char::from(piece)).unwrap();
+
+            for _ in 0..bb.0.count_ones() {
+                let i = bb.pop_lsb() as usize;
+
+                material += PIECE_SCORES[piece as usize] * color.direction() as i32;
+                tables += COLORED_PIECE_SQUARE_TABLES[color as usize][piece as usize][i]
+                    * color.direction() as i32;
+            }
+        }
+
+        counts.push('\n');
+    }
+
+    let back_rank = |color| is_back_rank_weak(board, color) as i32 * BACK_RANK_PENALTY;
+
+    let mut dump = String::new();
+
+    writeln!(dump, "Board\n{board}\n").unwrap();
+    writeln!(dump, "Material\n{counts}").unwrap();
+    writeln!(dump, "Position").unwrap();
+    writeln!(dump, "Side to move: {:?}", board.active_color).unwrap();
+    writeln!(dump, "Phase: {}/{OPENING_PHASE}\n", phase(board)).unwrap();
+    writeln!(dump, "Evaluation").unwrap();
+    writeln!(dump, "Material: {material}").unwrap();
+    writeln!(dump, "Piece-square tables: {tables}").unwrap();
+    writeln!(
+        dump,
+        "King danger: {}",
+        king_danger(board, move_gen, Color::Black) - king_danger(board, move_gen, Color::White)
+    )
+    .unwrap();
+    writeln!(
+        dump,
+        "Rook files: {}",
+        rook_file_bonus(board, Color::White) - rook_file_bonus(board, Color::Black)
+    )
+    .unwrap();
+    writeln!(
+        dump,
+        "Back rank: {}",
+        back_rank(Color::Black) - back_rank(Color::White)
+    )
+    .unwrap();
+
+    if is_opposite_bishop_endgame(board) {
+        writeln!(
+            dump,
+            "Opposite-colored bishops: scaled to {OPPOSITE_BISHOPS_SCALE}%"
+        )
+        .unwrap();
+    }
+
+    write!(dump, "Total: {}", evaluate(board, move_gen)).unwrap();
+
+    dump
+}
+
 #[cfg(test)]
 pub mod eval_tests {
-    use chress::move_gen::MoveGen;
+    use chress::board::START_FEN;
 
     use super::*;
 
@@ -107,7 +387,7 @@ pub mod eval_tests {
         )
         .unwrap();
 
-        println!("{}", evaluate(&board));
+        println!("{}", evaluate(&board, &move_gen));
     }
 
     #[test]
@@ -119,7 +399,7 @@ pub mod eval_tests {
         )
         .unwrap();
 
-        println!("{}", evaluate(&board));
+        println!("{}", evaluate(&board, &move_gen));
     }
 
     #[test]
@@ -131,6 +411,217 @@ pub mod eval_tests {
         )
         .unwrap();
 
-        println!("{}", evaluate(&board));
+        println!("{}", evaluate(&board, &move_gen));
+    }
+
+    #[test]
+    fn eval_relative() {
+        let move_gen = MoveGen::new();
+        let white = Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+        let black = Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR b KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert!(evaluate_relative(&white, &move_gen) < 0);
+        assert_eq!(
+            evaluate_relative(&black, &move_gen) - TEMPO,
+            -(evaluate_relative(&white, &move_gen) - TEMPO)
+        );
+    }
+
+    #[test]
+    fn eval_relative_tempo() {
+        let move_gen = MoveGen::new();
+
+        for fen in [
+            START_FEN,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            assert_eq!(evaluate(&board, &move_gen), 0);
+            assert_eq!(evaluate_relative(&board, &move_gen), TEMPO);
+        }
+    }
+
+    #[test]
+    fn phase_of_start_and_endgame() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(phase(&Board::default()), OPENING_PHASE);
+
+        let board = Board::from_fen("2b3k1/pp6/8/8/8/8/PPP5/2BR2K1 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(phase(&board), 4);
+    }
+
+    #[test]
+    fn opposite_colored_bishops_are_drawish() {
+        let move_gen = MoveGen::new();
+
+        // White is a pawn up in both, with bishops on c1 and either c8 or f8
+        let opposite =
+            Board::from_fen("2b3k1/pp6/8/8/8/8/PPP5/2B3K1 w - - 0 1", &move_gen).unwrap();
+        let same = Board::from_fen("5bk1/pp6/8/8/8/8/PPP5/2B3K1 w - - 0 1", &move_gen).unwrap();
+
+        assert!(is_opposite_bishop_endgame(&opposite));
+        assert!(!is_opposite_bishop_endgame(&same));
+
+        assert!(evaluate(&same, &move_gen) > 0);
+        assert!(evaluate(&opposite, &move_gen).abs() * 2 < evaluate(&same, &move_gen));
+    }
+
+    #[test]
+    fn black_tables_are_white_tables_flipped() {
+        for piece in Piece::ALL {
+            for square in Square::ALL {
+                assert_eq!(
+                    COLORED_PIECE_SQUARE_TABLES[Color::Black as usize][piece as usize]
+                        [square as usize],
+                    COLORED_PIECE_SQUARE_TABLES[Color::White as usize][piece as usize]
+                        [square.flip_vertical() as usize],
+                    "{piece:?} on {square}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mirrored_position_evaluates_opposite() {
+        let move_gen = MoveGen::new();
+
+        // The same position with colors swapped and the board flipped vertically
+        let white = Board::from_fen(
+            "r1bqk2r/ppp2ppp/2n2n2/3pp3/1bPP4/2N2N2/PP2PPPP/R1BQKB1R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+        let black = Board::from_fen(
+            "r1bqkb1r/pp2pppp/2n2n2/1Bpp4/3PP3/2N2N2/PPP2PPP/R1BQK2R b KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert_ne!(evaluate(&white, &move_gen), 0);
+        assert_eq!(evaluate(&white, &move_gen), -evaluate(&black, &move_gen));
+    }
+
+    #[test]
+    fn queen_near_king_is_dangerous() {
+        let move_gen = MoveGen::new();
+
+        // The same material, with the black queen either far away on c8 or on h4 bearing
+        // down on the castled white king
+        let quiet =
+            Board::from_fen("2q1rrk1/5ppp/8/8/8/8/5PPP/3QR1K1 w - - 0 1", &move_gen).unwrap();
+        let attacked =
+            Board::from_fen("4rrk1/5ppp/8/8/7q/8/5PPP/3QR1K1 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(king_danger(&quiet, &move_gen, Color::White), 0);
+        assert!(king_danger(&attacked, &move_gen, Color::White) > 0);
+
+        assert!(evaluate(&attacked, &move_gen) < evaluate(&quiet, &move_gen) - 20);
+    }
+
+    #[test]
+    fn king_zone_includes_king_square() {
+        assert_eq!(king_zone(Square::E4).0.count_ones(), 9);
+        assert_eq!(king_zone(Square::H1).0.count_ones(), 4);
+        assert!(!(king_zone(Square::A8) & Square::A8.bitboard()).is_empty());
+    }
+
+    #[test]
+    fn back_rank_king_without_luft() {
+        let move_gen = MoveGen::new();
+
+        // Identical except for the h-pawn, which gives the white king luft on h2 when
+        // pushed to h3
+        let trapped = Board::from_fen("3r2k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1", &move_gen).unwrap();
+        let luft = Board::from_fen("3r2k1/5ppp/8/8/8/7P/5PP1/6K1 w - - 0 1", &move_gen).unwrap();
+
+        assert!(is_back_rank_weak(&trapped, Color::White));
+        assert!(!is_back_rank_weak(&luft, Color::White));
+
+        // White has no rook or queen to exploit Black's back rank
+        assert!(!is_back_rank_weak(&trapped, Color::Black));
+
+        // A rook on the back rank guards it
+        let guarded = Board::from_fen("3r2k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", &move_gen).unwrap();
+
+        assert!(!is_back_rank_weak(&guarded, Color::White));
+        assert!(!is_back_rank_weak(&Board::default(), Color::White));
+
+        let penalty = evaluate(&luft, &move_gen) - evaluate(&trapped, &move_gen);
+        let pawn_table = &COLORED_PIECE_SQUARE_TABLES[Color::White as usize][Piece::Pawn as usize];
+        let table_difference = pawn_table[Square::H3 as usize] - pawn_table[Square::H2 as usize];
+
+        assert_eq!(penalty - table_difference, BACK_RANK_PENALTY);
+    }
+
+    #[test]
+    fn rooks_prefer_open_files() {
+        let move_gen = MoveGen::new();
+
+        // Only the d-file is open, and the e-file is semi-open for Black
+        let open =
+            Board::from_fen("6k1/ppp2ppp/8/8/8/4P3/PPP2PPP/3R2K1 w - - 0 1", &move_gen).unwrap();
+        let semi_open =
+            Board::from_fen("4r1k1/ppp2ppp/8/8/8/4P3/PPP2PPP/4R1K1 w - - 0 1", &move_gen).unwrap();
+        let closed =
+            Board::from_fen("6k1/ppp2ppp/8/8/8/4P3/PPP2PPP/4R1K1 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(rook_file_bonus(&open, Color::White), ROOK_OPEN_FILE_BONUS);
+        assert_eq!(rook_file_bonus(&closed, Color::White), 0);
+        assert_eq!(
+            rook_file_bonus(&semi_open, Color::Black),
+            ROOK_SEMI_OPEN_FILE_BONUS
+        );
+
+        // d1 and e1 are worth the same in the rook table
+        assert_eq!(
+            evaluate(&open, &move_gen) - evaluate(&closed, &move_gen),
+            ROOK_OPEN_FILE_BONUS
+        );
+    }
+
+    #[test]
+    fn pretty_eval_sections() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        let dump = pretty_eval(&board, &move_gen);
+
+        for header in ["Board", "Material", "Position", "Evaluation"] {
+            assert!(dump.lines().any(|line| line == header), "{header}");
+        }
+
+        assert!(dump.contains("White: 2n 2b 2r 0q 1k 8p"));
+        assert!(dump.contains("Side to move: White"));
+
+        let value = |prefix: &str| {
+            let line = dump.lines().find(|line| line.starts_with(prefix)).unwrap();
+            line[prefix.len()..].parse::<i32>().unwrap()
+        };
+
+        let terms = [
+            "Material: ",
+            "Piece-square tables: ",
+            "King danger: ",
+            "Rook files: ",
+            "Back rank: ",
+        ];
+
+        assert_eq!(value("Total: "), evaluate(&board, &move_gen));
+        assert_eq!(terms.map(value).iter().sum::<i32>(), value("Total: "));
     }
 }
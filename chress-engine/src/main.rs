@@ -1,9 +1,6 @@
 use std::{
     io::{self, BufRead},
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
-    },
+    sync::Arc,
 };
 
 use chress::{board::Board, move_gen::MoveGen};
@@ -13,9 +10,6 @@ use chress_engine::search::SearchManager;
 extern crate chress;
 
 fn main() -> std::io::Result<()> {
-    // Threading
-    let cancelled = Arc::new(Mutex::new(AtomicBool::new(false)));
-
     let board = Board::default();
     let move_gen = Arc::new(MoveGen::new());
 
@@ -48,11 +42,10 @@ fn main() -> std::io::Result<()> {
             }
 
             "stop" => {
-                // Cancel the current search
-                cancelled.lock().unwrap().store(true, Ordering::Relaxed);
-
-                // Write the best move
-                println!("bestmove {}", search_manager.best_move());
+                // Cancels the current search and writes the best move
+                if search_manager.running {
+                    search_manager.stop();
+                }
             }
 
             _ => continue,
@@ -9,30 +9,72 @@ use std::{
 };
 
 use chress::{
-    board::{r#move::Move, Board},
+    board::{piece::Piece, r#move::Move, Board},
     move_gen::MoveGen,
 };
 
-use crate::evaluation::evaluate;
+use crate::evaluation::evaluate_relative;
 
-#[derive(Debug, Clone, Copy)]
+/// Bounds of the search window. Kept well within `i32` so that negating any score is
+/// always safe.
+pub const MAX_SCORE: i32 = 32000;
+pub const MIN_SCORE: i32 = -MAX_SCORE;
+
+/// Score of being checkmated at the root, reduced by the distance to mate so that
+/// shorter mates are preferred.
+pub const MATE_SCORE: i32 = 30000;
+
+/// Positions further than this from the root are no longer extended, so that long
+/// forced sequences can't grow the search indefinitely.
+const MAX_EXTENSION_PLY: u8 = 64;
+
+/// Plies the triangular principal variation table has room for. `ply_from_root` is a
+/// `u8`, so no line can be longer.
+const MAX_PLY: usize = u8::MAX as usize + 1;
+
+/// Captures which lose more material than this by static exchange evaluation are skipped
+/// in quiescence search.
+pub const QSEARCH_SEE_THRESHOLD: i32 = 0;
+
+/// Static evaluations are clamped to this, so that they can never be mistaken for a
+/// mate score.
+pub const MAX_EVAL: i32 = MATE_SCORE - MAX_PLY as i32 - 1;
+
+#[derive(Debug, Clone, Copy, Default)]
 pub enum MoveTime {
+    #[default]
     Infinite,
     Millis(u32),
 }
 
-impl Default for MoveTime {
-    fn default() -> Self {
-        Self::Infinite
-    }
-}
-
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct SearchSettings {
     pub ponder: bool,
     pub moves_to_go: Option<u16>,
     pub max_depth: Option<u8>,
     pub movetime: MoveTime,
+    /// Shuffles the root moves using this seed, so that ties between equally scored
+    /// moves are broken differently for different seeds. Useful for varied self-play.
+    pub root_shuffle_seed: Option<u64>,
+    /// Quiescence search skips captures whose static exchange evaluation is below this
+    /// threshold, unless in check. `None` searches every capture.
+    pub qsearch_see_threshold: Option<i32>,
+    /// Stops the search once this many nodes have been searched.
+    pub max_nodes: Option<u64>,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            ponder: false,
+            moves_to_go: None,
+            max_depth: None,
+            movetime: MoveTime::default(),
+            root_shuffle_seed: None,
+            qsearch_see_threshold: Some(QSEARCH_SEE_THRESHOLD),
+            max_nodes: None,
+        }
+    }
 }
 
 /// Manages all searching threads and shared data
@@ -112,8 +154,11 @@ impl SearchManager {
                         }
                     }
 
-                    cancelled.lock().unwrap().store(true, Ordering::Relaxed);
-                    println!("bestmove {}", *best_move.lock().unwrap());
+                    // A search which reached its depth or node limit has already printed
+                    // its best move
+                    if !cancelled.lock().unwrap().swap(true, Ordering::Relaxed) {
+                        println!("bestmove {}", *best_move.lock().unwrap());
+                    }
                 });
             }
             _ => {
@@ -128,7 +173,12 @@ impl SearchManager {
         let best_eval = Arc::clone(&self.best_eval);
 
         // Start new search
-        let new_search = Search::new(position, move_gen, cancelled, best_move, best_eval);
+        let mut new_search = Search::new(position, move_gen, cancelled, best_move, best_eval);
+        new_search.root_shuffle_seed = self.settings.root_shuffle_seed;
+        new_search.qsearch_see_threshold = self.settings.qsearch_see_threshold;
+        new_search.max_nodes = self.settings.max_nodes;
+        new_search.max_depth = self.settings.max_depth;
+
         self.searches.push(new_search.start());
 
         self.running = true;
@@ -142,18 +192,21 @@ impl SearchManager {
 
         self.canceller = None;
 
-        self.cancelled
-            .lock()
-            .unwrap()
-            .store(true, Ordering::Relaxed);
+        // A search which reached its depth or node limit, or ran out of time, has already
+        // printed its best move
+        let already_cancelled = self.cancelled.lock().unwrap().swap(true, Ordering::Relaxed);
 
         self.running = false;
 
-        for _ in 0..self.searches.len() {
-            drop(self.searches.pop());
+        // Wait for the searches to see the cancellation, so that the best move can't
+        // change after it has been printed
+        for search in self.searches.drain(..) {
+            let _ = search.join();
         }
 
-        println!("bestmove {}", self.best_move());
+        if !already_cancelled {
+            println!("bestmove {}", self.best_move());
+        }
     }
 
     pub fn best_move(&self) -> Move {
@@ -165,12 +218,41 @@ impl SearchManager {
     }
 }
 
+/// Fisher-Yates shuffle driven by a xorshift generator, so the same seed always gives
+/// the same order.
+fn shuffle(moves: &mut [Move], seed: u64) {
+    // Xorshift gets stuck on a state of 0
+    let mut state = seed ^ 0x9E37_79B9_7F4A_7C15;
+
+    for i in (1..moves.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        moves.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
 /// Represents a single thread performing a search
 #[derive(Debug, Clone)]
 pub struct Search {
     board: Board,
     best_move_so_far: Move,
     best_eval_so_far: i32,
+    root_shuffle_seed: Option<u64>,
+    qsearch_see_threshold: Option<i32>,
+    max_nodes: Option<u64>,
+    max_depth: Option<u8>,
+    /// Whether to use principal variation search rather than plain alpha-beta
+    pvs: bool,
+    nodes: u64,
+    /// Hashes of the positions before the current one, oldest first, for detecting
+    /// repetitions. A hash is pushed before every move made in `alpha_beta`, including null
+    /// moves, and popped after unmaking it.
+    history: Vec<u64>,
+    /// Triangular principal variation table: `pv_table[ply]` holds the best line found from
+    /// the node at that ply, which is the best move there followed by `pv_table[ply + 1]`.
+    pv_table: Vec<Vec<Move>>,
 
     // Shared data
     move_gen: Arc<MoveGen>,
@@ -191,6 +273,14 @@ impl Search {
             board,
             best_move_so_far: Move::NULLMOVE,
             best_eval_so_far: 0,
+            root_shuffle_seed: None,
+            qsearch_see_threshold: Some(QSEARCH_SEE_THRESHOLD),
+            max_nodes: None,
+            max_depth: None,
+            pvs: true,
+            nodes: 0,
+            history: Vec::new(),
+            pv_table: vec![Vec::new(); MAX_PLY],
 
             // Shared data
             move_gen,
@@ -200,20 +290,83 @@ impl Search {
         }
     }
 
+    /// Searches the position to a fixed depth on the current thread, returning the
+    /// evaluation for the side to move and the principal variation.
+    pub fn fixed_depth(board: Board, move_gen: Arc<MoveGen>, depth: u8) -> (i32, Vec<Move>) {
+        Self::fixed_depth_with_settings(board, move_gen, depth, SearchSettings::default())
+    }
+
+    /// Same as [`Search::fixed_depth`], applying the settings which don't concern time
+    /// management.
+    pub fn fixed_depth_with_settings(
+        board: Board,
+        move_gen: Arc<MoveGen>,
+        depth: u8,
+        settings: SearchSettings,
+    ) -> (i32, Vec<Move>) {
+        let mut search = Self::new(
+            board,
+            move_gen,
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.root_shuffle_seed = settings.root_shuffle_seed;
+        search.qsearch_see_threshold = settings.qsearch_see_threshold;
+        search.max_nodes = settings.max_nodes;
+
+        let eval = search.alpha_beta(0, MIN_SCORE, MAX_SCORE, depth);
+
+        (eval, search.pv().to_vec())
+    }
+
+    /// The principal variation found by the last search, starting with the best move.
+    pub fn pv(&self) -> &[Move] {
+        &self.pv_table[0]
+    }
+
+    /// Formats a UCI `info` line for a completed iteration of iterative deepening.
+    fn info(&self, depth: u8) -> String {
+        let score = self.best_eval_so_far;
+
+        let score = if score.abs() > MATE_SCORE - MAX_PLY as i32 {
+            // Moves rather than plies until mate, negative when being mated
+            let plies = MATE_SCORE - score.abs();
+            format!("mate {}", score.signum() * (plies + 1) / 2)
+        } else {
+            format!("cp {score}")
+        };
+
+        let pv = self
+            .pv()
+            .iter()
+            .map(Move::to_string)
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        format!(
+            "info depth {depth} score {score} nodes {} pv {pv}",
+            self.nodes
+        )
+    }
+
     pub fn start(mut self) -> JoinHandle<()> {
         thread::spawn(move || self.start_iterative_deepening())
     }
 
     fn start_iterative_deepening(&mut self) {
+        let last_depth = self.max_depth.unwrap_or(u8::MAX).min(253);
         let mut i = 1;
 
-        while i < 254 {
-            self.alpha_beta(0, -999999, 999999, i);
+        while i <= last_depth {
+            self.alpha_beta(0, MIN_SCORE, MAX_SCORE, i);
 
-            if self.cancelled.lock().unwrap().load(Ordering::Relaxed) {
+            if self.should_stop() {
                 break;
             }
 
+            println!("{}", self.info(i));
+
             *self.best_move.lock().unwrap() = self.best_move_so_far;
             self.best_eval
                 .lock()
@@ -222,26 +375,111 @@ impl Search {
 
             i += 1;
         }
+
+        // Stopped before the first iteration finished, so fall back to the best move of
+        // the unfinished iteration, or any legal move if none was searched
+        let mut best_move = self.best_move.lock().unwrap();
+
+        if *best_move == Move::NULLMOVE {
+            *best_move = if self.best_move_so_far != Move::NULLMOVE {
+                self.best_move_so_far
+            } else {
+                let mut moves = Vec::new();
+                self.move_gen.legal_moves(&self.board, &mut moves);
+                moves.first().copied().unwrap_or(Move::NULLMOVE)
+            };
+        }
+
+        drop(best_move);
+
+        // Running out of time or being stopped prints the best move elsewhere, but only
+        // the search itself knows when it reaches its depth or node limit. Cancelling
+        // stops the time limit from printing the best move again
+        if !self.cancelled.lock().unwrap().swap(true, Ordering::Relaxed) {
+            println!("bestmove {}", *self.best_move.lock().unwrap());
+        }
+    }
+
+    /// Checks whether the search was cancelled or has used up its node budget.
+    fn should_stop(&self) -> bool {
+        self.cancelled.lock().unwrap().load(Ordering::Relaxed)
+            || self
+                .max_nodes
+                .is_some_and(|max_nodes| self.nodes >= max_nodes)
     }
 
     fn alpha_beta(&mut self, ply_from_root: u8, mut alpha: i32, beta: i32, depth: u8) -> i32 {
-        if self.cancelled.lock().unwrap().load(Ordering::Relaxed) {
+        let ply = ply_from_root as usize;
+        self.pv_table[ply].clear();
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        // Repeating a position can't be better than the first time it occurred, so it is
+        // scored as a draw
+        if ply_from_root > 0 && self.board.repetitions(&self.history) > 0 {
             return 0;
         }
 
         if depth == 0 {
-            return evaluate(&self.board);
+            return self.quiescence(ply_from_root, alpha, beta);
         }
 
+        self.nodes += 1;
+
         let mut moves = Vec::new();
         self.move_gen.legal_moves(&self.board, &mut moves);
 
-        for mv in moves {
+        if let (0, Some(seed)) = (ply_from_root, self.root_shuffle_seed) {
+            shuffle(&mut moves, seed);
+        }
+
+        // Checkmate or stalemate
+        if moves.is_empty() {
+            return if self.board.is_in_check(&self.move_gen) {
+                -MATE_SCORE + ply_from_root as i32
+            } else {
+                0
+            };
+        }
+
+        // Search checking moves first, as they are the most forcing
+        if let Some(check_info) = self.board.check_info(&self.move_gen) {
+            let board = &self.board;
+            let move_gen = &self.move_gen;
+
+            moves.sort_by_key(|&mv| !board.gives_check(move_gen, &check_info, mv));
+        }
+
+        // One-reply extension: forced moves (see `Board::is_forced`) cost nothing to
+        // search deeper, as the position doesn't branch
+        let extension = (moves.len() == 1 && ply_from_root < MAX_EXTENSION_PLY) as u8;
+
+        let new_depth = depth - 1 + extension;
+
+        for (i, mv) in moves.into_iter().enumerate() {
+            self.history.push(self.board.hash);
             let move_data = self.board.make_move(mv).unwrap();
-            let score = -self.alpha_beta(ply_from_root + 1, -beta, -alpha, depth - 1);
+
+            // Principal variation search: after the first move, only check whether each
+            // move beats alpha with a null window, and search it fully if it does
+            let score = if i == 0 || !self.pvs {
+                -self.alpha_beta(ply_from_root + 1, -beta, -alpha, new_depth)
+            } else {
+                let score = -self.alpha_beta(ply_from_root + 1, -alpha - 1, -alpha, new_depth);
+
+                if score > alpha && score < beta {
+                    -self.alpha_beta(ply_from_root + 1, -beta, -alpha, new_depth)
+                } else {
+                    score
+                }
+            };
+
             self.board.unmake_move(move_data).unwrap();
+            self.history.pop();
 
-            if self.cancelled.lock().unwrap().load(Ordering::Relaxed) {
+            if self.should_stop() {
                 break;
             }
 
@@ -255,9 +493,395 @@ impl Search {
                     self.best_eval_so_far = score;
                 }
                 alpha = score;
+
+                let (current, deeper) = self.pv_table.split_at_mut(ply + 1);
+                let pv = &mut current[ply];
+
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(deeper.first().map_or(&[], Vec::as_slice));
+            }
+        }
+
+        alpha
+    }
+
+    /// Searches captures until the position is quiet, so that positions aren't evaluated
+    /// in the middle of an exchange. When in check, every move is searched instead.
+    fn quiescence(&mut self, ply_from_root: u8, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+
+        let in_check = self.board.is_in_check(&self.move_gen);
+
+        let mut moves = Vec::new();
+        self.move_gen.legal_moves(&self.board, &mut moves);
+
+        if in_check {
+            if moves.is_empty() {
+                return -MATE_SCORE + ply_from_root as i32;
+            }
+        } else {
+            // Standing pat: the side to move isn't forced to capture
+            let stand_pat =
+                evaluate_relative(&self.board, &self.move_gen).clamp(-MAX_EVAL, MAX_EVAL);
+
+            if stand_pat >= beta {
+                return beta;
+            }
+
+            alpha = alpha.max(stand_pat);
+
+            let board = &self.board;
+            let enemy_pieces = board.enemy_pieces();
+            let en_passant_square = board.en_passant_square();
+
+            moves.retain(|&mv| {
+                let is_en_passant = Some(mv.to()) == en_passant_square
+                    && board.piece_at(mv.from()) == Some(Piece::Pawn);
+
+                !(enemy_pieces & mv.to().bitboard()).is_empty() || is_en_passant
+            });
+
+            if let Some(threshold) = self.qsearch_see_threshold {
+                moves.retain(|&mv| board.see_ge(&self.move_gen, mv, threshold));
             }
+
+            board.order_captures(&self.move_gen, &mut moves);
+        }
+
+        for mv in moves {
+            let move_data = self.board.make_move(mv).unwrap();
+            let score = -self.quiescence(ply_from_root.saturating_add(1), -beta, -alpha);
+            self.board.unmake_move(move_data).unwrap();
+
+            if self.should_stop() {
+                break;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+
+            alpha = alpha.max(score);
         }
 
         alpha
     }
 }
+
+#[cfg(test)]
+mod search_tests {
+    use chress::board::START_FEN;
+
+    use super::*;
+
+    #[test]
+    fn fixed_depth_finds_mate() {
+        let move_gen = Arc::new(MoveGen::new());
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", &move_gen).unwrap();
+
+        let (eval, pv) = Search::fixed_depth(board, move_gen, 3);
+
+        assert_eq!(eval, MATE_SCORE - 1);
+        assert_eq!(pv[0].to_string(), "a1a8");
+    }
+
+    #[test]
+    fn fixed_depth_black_to_move() {
+        let move_gen = Arc::new(MoveGen::new());
+        let board = Board::from_fen("r3k3/8/8/8/8/8/5PPP/6K1 b - - 0 1", &move_gen).unwrap();
+
+        let (eval, pv) = Search::fixed_depth(board, move_gen, 3);
+
+        assert_eq!(eval, MATE_SCORE - 1);
+        assert_eq!(pv[0].to_string(), "a8a1");
+    }
+
+    #[test]
+    fn one_reply_extension() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        // c4 is White's only move, after which Re1 is mate. Without extending the forced
+        // move, the mate is beyond a depth 2 search
+        let board = Board::from_fen("4r3/p7/P7/8/8/2P4p/5k1P/7K w - - 0 1", &move_gen).unwrap();
+
+        assert!(board.is_forced(&move_gen));
+
+        let (eval, pv) = Search::fixed_depth(board, move_gen, 2);
+
+        assert_eq!(eval, -MATE_SCORE + 2);
+        assert_eq!(pv[0].to_string(), "c3c4");
+    }
+
+    #[test]
+    fn root_shuffle_breaks_ties() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        // At depth 1, Nf3 and Nc3 are the best moves and score the same
+        let best_move = |seed| {
+            let settings = SearchSettings {
+                root_shuffle_seed: Some(seed),
+                ..Default::default()
+            };
+
+            let (_, pv) = Search::fixed_depth_with_settings(
+                Board::default(),
+                Arc::clone(&move_gen),
+                1,
+                settings,
+            );
+
+            pv[0].to_string()
+        };
+
+        let mut best_moves = (0..16).map(best_move).collect::<Vec<String>>();
+
+        assert_eq!(best_move(3), best_move(3));
+
+        best_moves.sort();
+        best_moves.dedup();
+
+        assert_eq!(best_moves, ["b1c3", "g1f3"]);
+    }
+
+    /// Searches to a fixed depth, returning the evaluation and the number of nodes searched
+    fn search_nodes(fen: &str, depth: u8, qsearch_see_threshold: Option<i32>) -> (i32, u64) {
+        let move_gen = Arc::new(MoveGen::new());
+        let board = Board::from_fen(fen, &move_gen).unwrap();
+
+        let mut search = Search::new(
+            board,
+            move_gen,
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.qsearch_see_threshold = qsearch_see_threshold;
+
+        let eval = search.alpha_beta(0, MIN_SCORE, MAX_SCORE, depth);
+
+        (eval, search.nodes)
+    }
+
+    #[test]
+    fn see_pruning_shrinks_quiescence() {
+        // Kiwipete is full of captures, most of which lose material
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+        let (_, pruned) = search_nodes(fen, 1, Some(QSEARCH_SEE_THRESHOLD));
+        let (_, unpruned) = search_nodes(fen, 1, None);
+
+        assert!(
+            pruned * 5 < unpruned,
+            "{pruned} nodes pruned, {unpruned} unpruned"
+        );
+    }
+
+    #[test]
+    fn history_restored_after_search() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        let mut search = Search::new(
+            Board::default(),
+            move_gen,
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.history = vec![1, 2, 3];
+
+        search.alpha_beta(0, MIN_SCORE, MAX_SCORE, 3);
+
+        assert_eq!(search.history, [1, 2, 3]);
+        assert_eq!(search.board, Board::default());
+    }
+
+    #[test]
+    fn repetition_scores_as_draw() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        // White is a queen down, but the position after Kg1 was already reached in the game
+        let fen = "6k1/8/8/q7/8/8/8/7K w - - 4 30";
+        let board = Board::from_fen(fen, &move_gen).unwrap();
+
+        let mut search = Search::new(
+            board,
+            Arc::clone(&move_gen),
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+
+        let mut repeated = board;
+        repeated.make_move(Move::try_from("h1g1").unwrap()).unwrap();
+        search.history = vec![0, repeated.hash, 0];
+
+        let eval = search.alpha_beta(0, MIN_SCORE, MAX_SCORE, 1);
+
+        assert_eq!(eval, 0);
+        assert_eq!(search.best_move_so_far.to_string(), "h1g1");
+    }
+
+    #[test]
+    fn node_limit_stops_search() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        let mut search = Search::new(
+            Board::default(),
+            move_gen,
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.max_nodes = Some(100_000);
+
+        search.start_iterative_deepening();
+
+        assert!(
+            (100_000..101_000).contains(&search.nodes),
+            "{} nodes searched",
+            search.nodes
+        );
+        assert_ne!(*search.best_move.lock().unwrap(), Move::NULLMOVE);
+    }
+
+    #[test]
+    fn pvs_searches_fewer_nodes() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        let search = |fen, pvs| {
+            let mut search = Search::new(
+                Board::from_fen(fen, &move_gen).unwrap(),
+                Arc::clone(&move_gen),
+                Arc::new(Mutex::new(AtomicBool::new(false))),
+                Arc::new(Mutex::new(Move::NULLMOVE)),
+                Arc::new(Mutex::new(AtomicI32::new(0))),
+            );
+            search.pvs = pvs;
+
+            let eval = search.alpha_beta(0, MIN_SCORE, MAX_SCORE, 4);
+
+            (eval, search.best_move_so_far, search.nodes)
+        };
+
+        for fen in [
+            START_FEN,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        ] {
+            let (eval, best_move, nodes) = search(fen, false);
+            let (pvs_eval, pvs_best_move, pvs_nodes) = search(fen, true);
+
+            assert_eq!((pvs_eval, pvs_best_move), (eval, best_move), "{fen}");
+            assert!(
+                pvs_nodes < nodes,
+                "{fen}: {pvs_nodes} nodes, {nodes} without PVS"
+            );
+        }
+    }
+
+    #[test]
+    fn pv_follows_forced_line() {
+        let move_gen = Arc::new(MoveGen::new());
+
+        // c4 is forced, and Re1 is the only mate in reply
+        let board = Board::from_fen("4r3/p7/P7/8/8/2P4p/5k1P/7K w - - 0 1", &move_gen).unwrap();
+
+        let (_, pv) = Search::fixed_depth(board, move_gen, 2);
+
+        assert_eq!(
+            pv.iter().map(Move::to_string).collect::<Vec<String>>(),
+            ["c3c4", "e8e1"]
+        );
+    }
+
+    #[test]
+    fn info_line_reports_pv() {
+        let move_gen = Arc::new(MoveGen::new());
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", &move_gen).unwrap();
+
+        let mut search = Search::new(
+            board,
+            move_gen,
+            Arc::new(Mutex::new(AtomicBool::new(false))),
+            Arc::new(Mutex::new(Move::NULLMOVE)),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.alpha_beta(0, MIN_SCORE, MAX_SCORE, 3);
+
+        let info = search.info(3);
+
+        assert!(
+            info.starts_with("info depth 3 score mate 1 nodes "),
+            "{info}"
+        );
+        assert!(info.ends_with(" pv a1a8"), "{info}");
+    }
+
+    #[test]
+    fn stop_returns_legal_move() {
+        let move_gen = Arc::new(MoveGen::new());
+        let board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        let mut legal_moves = Vec::new();
+        move_gen.legal_moves(&board, &mut legal_moves);
+
+        // Stopping an infinite search both after it has had time to search and straight
+        // away, before it can finish an iteration
+        for wait in [50, 0] {
+            let mut search_manager = SearchManager::new(Arc::clone(&move_gen));
+            search_manager.start_search(board);
+
+            thread::sleep(Duration::from_millis(wait));
+            search_manager.stop();
+
+            assert!(!search_manager.running);
+            assert!(
+                legal_moves.contains(&search_manager.best_move()),
+                "{}",
+                search_manager.best_move()
+            );
+        }
+    }
+
+    #[test]
+    fn depth_limit_ends_search() {
+        let move_gen = Arc::new(MoveGen::new());
+        let cancelled = Arc::new(Mutex::new(AtomicBool::new(false)));
+        let best_move = Arc::new(Mutex::new(Move::NULLMOVE));
+
+        let mut search = Search::new(
+            Board::default(),
+            Arc::clone(&move_gen),
+            Arc::clone(&cancelled),
+            Arc::clone(&best_move),
+            Arc::new(Mutex::new(AtomicI32::new(0))),
+        );
+        search.max_depth = Some(3);
+
+        // Returns on its own, without a time limit or being stopped
+        search.start_iterative_deepening();
+
+        let (_, pv) = Search::fixed_depth(Board::default(), move_gen, 3);
+
+        assert_eq!(search.pv(), pv);
+        assert_eq!(*best_move.lock().unwrap(), pv[0]);
+        assert!(cancelled.lock().unwrap().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn score_bounds_negate_safely() {
+        assert_eq!(MIN_SCORE.checked_neg(), Some(MAX_SCORE));
+        assert_eq!(MAX_SCORE.checked_neg(), Some(MIN_SCORE));
+
+        // Every mate score fits inside the window and outranks any static evaluation
+        const {
+            assert!(MATE_SCORE < MAX_SCORE);
+            assert!(MATE_SCORE - MAX_PLY as i32 > MAX_EVAL);
+        }
+    }
+}
@@ -1,59 +1,53 @@
-use std::{
-    io::{BufRead, Write},
-    path::Path,
+use std::{env, fs, path::Path};
+
+use chress::move_gen::MoveGen;
+use chress_test::{
+    game::{play_game, GameSettings},
+    parse_opening_book,
+    stats::{Sprt, SprtVerdict},
+    Engine, Session,
 };
 
-use chress::board::{r#move::Move, Board};
-use chress_test::Engine;
+const MAX_GAMES: u32 = 1000;
 
 // Note: Programs run in the workspace directory
 fn main() -> std::io::Result<()> {
-    let mut engine_1 =
+    let engine_1 =
         Engine::new("engine1".to_owned(), Path::new("target/release/chress_cli")).unwrap();
 
-    let mut engine_2 =
+    let engine_2 =
         Engine::new("engine2".to_owned(), Path::new("target/release/chress_cli")).unwrap();
 
-    let mut board = Board::default();
-    let mut moves: Vec<Move> = Vec::new();
-    let mut position_string = String::from("position startpos \n");
+    let mut session = Session::new(engine_1, engine_2);
 
-    let mut buf = String::new();
-
-    // Engine setup
-    engine_1.stdin.write_all(b"uci\nucinewgame\nisready\n")?;
-    engine_2.stdin.write_all(b"uci\nucinewgame\nisready\n")?;
-
-    // Game loop
-    loop {
-        buf.clear();
-
-        let engine = if moves.len() % 2 == 0 {
-            &mut engine_1
-        } else {
-            &mut engine_2
-        };
-
-        engine.stdin.write_all(position_string.as_bytes())?;
-        engine.stdin.write_all(b"go movetime 500\n")?;
-
-        engine.stdout.read_line(&mut buf)?;
-
-        let mv = Move::try_from(buf.as_ref()).unwrap();
-
-        position_string.push_str(buf.as_ref());
-        moves.push(mv);
-
-        // TODO: Implement game-end logic
-
-        // ?  Game ends are as follows:
-        // ?  - Stalemate
-        // ?  - Checkmate
-        // ?  - Insufficient material
+    // Optional FEN/EPD opening book
+    if let Some(path) = env::args().nth(1) {
+        session.openings = parse_opening_book(&fs::read_to_string(path)?);
+    }
 
-        // if let Some(game_end) = board.game_over() {
-        //     break;
-        // }
+    // Is engine_1 at least 10 Elo stronger, or no stronger at all?
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+
+    let settings = GameSettings::default();
+    let move_gen = MoveGen::new();
+
+    let verdict = session.run_match(MAX_GAMES, Some(sprt), |white, black, opening| {
+        play_game(white, black, opening, &settings, &move_gen)
+    });
+
+    println!(
+        "Score: +{} -{} ={} ({:.1} ± {:.1} Elo)",
+        session.wins,
+        session.losses,
+        session.draws,
+        session.elo_difference(),
+        session.elo_error_margin()
+    );
+
+    match verdict {
+        Some(SprtVerdict::AcceptH0) => println!("SPRT: H0 accepted"),
+        Some(SprtVerdict::AcceptH1) => println!("SPRT: H1 accepted"),
+        None => println!("SPRT: inconclusive"),
     }
 
     Ok(())
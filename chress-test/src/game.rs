@@ -0,0 +1,362 @@
+use std::time::Duration;
+
+use chress::{
+    board::{self, color::Color, r#move::Move, Board},
+    move_gen::MoveGen,
+};
+
+use crate::{position_command, Engine, GameLog, GameResult};
+
+pub struct GameSettings {
+    /// Time the engines are told to spend on each move, in milliseconds
+    pub movetime: u32,
+    /// Time an engine may take to respond with a move before it forfeits
+    pub move_timeout: Duration,
+    /// Games which haven't ended after this many plies are adjudicated as draws
+    pub max_plies: usize,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            movetime: 500,
+            move_timeout: Duration::from_secs(5),
+            max_plies: 1000,
+        }
+    }
+}
+
+fn loss_for(color: Color) -> GameResult {
+    match color {
+        Color::White => GameResult::BlackWins,
+        Color::Black => GameResult::WhiteWins,
+    }
+}
+
+/// The result of the game if it has ended by checkmate, stalemate, insufficient material or
+/// the fifty-move rule.
+fn game_end(board: &Board, move_gen: &MoveGen) -> Option<GameResult> {
+    board.game_over(move_gen).map(|result| match result {
+        board::GameResult::Checkmate(winner) => loss_for(winner.inverse()),
+        _ => GameResult::Draw,
+    })
+}
+
+/// Plays a game starting from `opening`. An engine which stops responding or plays an
+/// illegal move forfeits.
+///
+/// # Panics
+///
+/// Panics if `opening` isn't a valid FEN.
+pub fn play_game(
+    white: &mut Engine,
+    black: &mut Engine,
+    opening: &str,
+    settings: &GameSettings,
+    move_gen: &MoveGen,
+) -> GameLog {
+    let mut board = Board::from_fen(opening, move_gen).expect("invalid opening FEN");
+    let mut moves: Vec<Move> = Vec::new();
+    let mut fens = Vec::new();
+    // Hashes of the positions before each move, for spotting repetitions
+    let mut history = Vec::new();
+
+    // Engine setup, which also drains any output left over from the previous game
+    for (engine, color) in [(&mut *white, Color::White), (&mut *black, Color::Black)] {
+        let ready = engine
+            .send("uci\nucinewgame\n")
+            .and_then(|_| engine.wait_ready(settings.move_timeout));
+
+        if ready.is_err() {
+            return GameLog::new(opening, loss_for(color));
+        }
+    }
+
+    // Game loop
+    let result = loop {
+        let (engine, color) = if moves.len().is_multiple_of(2) {
+            (&mut *white, Color::White)
+        } else {
+            (&mut *black, Color::Black)
+        };
+
+        let response = engine.best_move(
+            &position_command(opening, &moves),
+            settings.movetime,
+            settings.move_timeout,
+        );
+
+        let Ok(Ok(mv)) = response.map(|mv| Move::try_from(mv.as_str())) else {
+            break loss_for(color);
+        };
+
+        history.push(board.hash);
+
+        if board.make_move_checked(mv, move_gen).is_err() {
+            break loss_for(color);
+        }
+
+        moves.push(mv);
+        fens.push(board.fen());
+
+        if let Some(result) = game_end(&board, move_gen) {
+            break result;
+        }
+
+        if board.repetitions(&history) >= 2 {
+            break GameResult::Draw;
+        }
+
+        if moves.len() >= settings.max_plies {
+            break GameResult::Draw;
+        }
+    };
+
+    GameLog {
+        result,
+        opening: opening.to_owned(),
+        moves,
+        fens,
+    }
+}
+
+#[cfg(test)]
+mod game_tests {
+    use std::process::Command;
+
+    use chress::board::START_FEN;
+
+    use super::*;
+
+    /// An engine which runs a shell script instead of a real engine
+    fn mock_engine(id: &str, script: &str) -> Engine {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+
+        Engine::from_command(id.to_owned(), command).unwrap()
+    }
+
+    /// An engine which plays `moves` in order, one each time it is asked to search
+    fn scripted_engine(id: &str, moves: &[&str]) -> Engine {
+        let script = format!(
+            "set -- {}; while read line; do case $line in go*) echo \"bestmove $1\"; shift;; isready) echo readyok;; esac; done",
+            moves.join(" ")
+        );
+
+        mock_engine(id, &script)
+    }
+
+    #[test]
+    fn unresponsive_engine_forfeits() {
+        let mut white = mock_engine("white", "exec sleep 60");
+        let mut black = mock_engine("black", "exec sleep 60");
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::BlackWins);
+        assert!(log.moves.is_empty());
+    }
+
+    #[test]
+    fn crashed_engine_forfeits() {
+        // Plays e2e4 when asked for its first move, then exits
+        let white = "while read line; do case $line in go*) echo 'bestmove e2e4'; exit;; \
+                     isready) echo readyok;; esac; done";
+        let black = "while read line; do case $line in go*) echo 'bestmove e7e5';; \
+                     isready) echo readyok;; esac; done";
+
+        let mut white = mock_engine("white", white);
+        let mut black = mock_engine("black", black);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::BlackWins);
+        assert_eq!(log.moves.len(), 2);
+    }
+
+    #[test]
+    fn late_move_not_used_in_next_game() {
+        // Answers its first search after the timeout, then plays d2d4 straight away
+        let slow = "n=0; while read line; do case $line in \
+                    go*) n=$((n + 1)); if [ $n = 1 ]; then sleep 0.7; echo 'bestmove e2e4'; \
+                    else echo 'bestmove d2d4'; fi;; \
+                    isready) echo readyok;; esac; done";
+
+        let mut white = mock_engine("white", slow);
+        let mut black = scripted_engine("black", &["d7d5"]);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            max_plies: 2,
+            ..Default::default()
+        };
+        let move_gen = MoveGen::new();
+
+        let log = play_game(&mut white, &mut black, START_FEN, &settings, &move_gen);
+
+        assert_eq!(log.result, GameResult::BlackWins);
+        assert!(log.moves.is_empty());
+
+        let log = play_game(&mut white, &mut black, START_FEN, &settings, &move_gen);
+
+        assert_eq!(log.result, GameResult::Draw);
+        assert_eq!(
+            log.moves,
+            ["d2d4", "d7d5"].map(|mv| Move::try_from(mv).unwrap())
+        );
+    }
+
+    #[test]
+    fn recorded_fens_replay_game() {
+        let mut white = scripted_engine("white", &["e2e4", "g1f3", "f1c4"]);
+        let mut black = scripted_engine("black", &["d7d5", "d5e4", "c8g4"]);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            max_plies: 6,
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::Draw);
+        assert_eq!(log.moves.len(), 6);
+        assert_eq!(log.fens.len(), 6);
+
+        let mut board = Board::try_from(log.opening.as_str()).unwrap();
+
+        for (mv, fen) in log.moves.iter().zip(&log.fens) {
+            board.make_move(*mv).unwrap();
+
+            assert_eq!(&board.fen(), fen);
+        }
+
+        assert_eq!(
+            log.fens.last().unwrap(),
+            "rn1qkbnr/ppp1pppp/8/8/2B1p1b1/5N2/PPPP1PPP/RNBQK2R w KQkq - 2 4"
+        );
+    }
+
+    #[test]
+    fn threefold_repetition_ends_game() {
+        let mut white = scripted_engine("white", &["g1f3", "f3g1"].repeat(5));
+        let mut black = scripted_engine("black", &["b8c6", "c6b8"].repeat(5));
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            max_plies: 20,
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::Draw);
+        // The start position occurs for the third time after the eighth ply
+        assert_eq!(log.moves.len(), 8);
+        assert_eq!(
+            log.fens.last().unwrap(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 8 5"
+        );
+    }
+
+    #[test]
+    fn illegal_move_forfeits() {
+        let mut white = scripted_engine("white", &["e2e4", "g1f3"]);
+        // The king can't move onto its own pawn
+        let mut black = scripted_engine("black", &["e8e7"]);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::WhiteWins);
+        assert_eq!(log.moves, [Move::try_from("e2e4").unwrap()]);
+    }
+
+    #[test]
+    fn checkmate_ends_game() {
+        let mut white = scripted_engine("white", &["f2f3", "g2g4"]);
+        let mut black = scripted_engine("black", &["e7e5", "d8h4"]);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            START_FEN,
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::BlackWins);
+        assert_eq!(log.moves.len(), 4);
+    }
+
+    #[test]
+    fn stalemate_ends_game() {
+        let mut white = scripted_engine("white", &["c5b6"]);
+        let mut black = scripted_engine("black", &[]);
+
+        let settings = GameSettings {
+            move_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        let log = play_game(
+            &mut white,
+            &mut black,
+            "k7/8/8/2Q5/8/8/8/7K w - - 0 1",
+            &settings,
+            &MoveGen::new(),
+        );
+
+        assert_eq!(log.result, GameResult::Draw);
+        assert_eq!(log.fens, ["k7/8/1Q6/8/8/8/8/7K b - - 1 1"]);
+    }
+}
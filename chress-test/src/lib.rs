@@ -1,37 +1,266 @@
+pub mod game;
+pub mod stats;
+
 use std::{
-    io::BufReader,
+    error::Error,
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
     path::Path,
-    process::{ChildStdin, ChildStdout, Command, Stdio},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
 };
 
-use chress::board::{color::Color, r#move::Move};
+use chress::board::{color::Color, r#move::Move, START_FEN};
+use stats::{Sprt, SprtVerdict};
+
+/// How long an engine is given to exit after being sent `quit`, before it is killed.
+const QUIT_GRACE_PERIOD: Duration = Duration::from_millis(200);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EngineError {
+    /// The engine didn't respond in time
+    Timeout,
+    /// The engine's process exited or closed its pipes
+    Disconnected,
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Error for EngineError {}
 
+/// An engine running in a child process, which is stopped when this is dropped.
 pub struct Engine {
     pub id: String,
     pub stdin: ChildStdin,
-    pub stdout: BufReader<ChildStdout>,
+    /// Lines of the engine's output, read on a separate thread so that reads can time out
+    lines: Receiver<String>,
+    process: Child,
 }
 
 impl Engine {
     pub fn new(id: String, path: &Path) -> std::io::Result<Self> {
-        let process = Command::new(path)
+        Self::from_command(id, Command::new(path))
+    }
+
+    /// Spawns an engine from a command, which allows passing arguments.
+    pub fn from_command(id: String, mut command: Command) -> std::io::Result<Self> {
+        let mut process = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()?;
 
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        let (tx, rx) = channel();
+
+        // Stops once the process closes its output or the engine is dropped
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
         Ok(Self {
             id,
-            stdin: process.stdin.unwrap(),
-            stdout: BufReader::new(process.stdout.unwrap()),
+            stdin: process.stdin.take().unwrap(),
+            lines: rx,
+            process,
+        })
+    }
+
+    /// Reads a line of output, without the trailing newline.
+    pub fn read_line(&self, timeout: Duration) -> Result<String, EngineError> {
+        self.lines
+            .recv_timeout(timeout)
+            .map_err(|error| match error {
+                RecvTimeoutError::Timeout => EngineError::Timeout,
+                RecvTimeoutError::Disconnected => EngineError::Disconnected,
+            })
+    }
+
+    /// Sends a command, which should end with a newline.
+    pub fn send(&mut self, command: &str) -> Result<(), EngineError> {
+        self.stdin
+            .write_all(command.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| EngineError::Disconnected)
+    }
+
+    /// Sends `isready` and waits for `readyok`. Any other output is skipped, including a
+    /// `bestmove` left over from an abandoned search.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), EngineError> {
+        self.send("isready\n")?;
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if self.read_line(remaining)?.trim() == "readyok" {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Sends a position and asks for a move, returning the move from the engine's
+    /// `bestmove` response. Any other output is skipped.
+    ///
+    /// If the engine times out, it is told to stop and its output is drained, so that a
+    /// late `bestmove` isn't taken as the answer to the next search.
+    pub fn best_move(
+        &mut self,
+        position: &str,
+        movetime: u32,
+        timeout: Duration,
+    ) -> Result<String, EngineError> {
+        self.send(position)?;
+        self.send(&format!("go movetime {movetime}\n"))?;
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.read_line(remaining) {
+                Ok(line) => line,
+                Err(EngineError::Timeout) => {
+                    let _ = self.send("stop\n");
+                    let _ = self.wait_ready(timeout);
+
+                    return Err(EngineError::Timeout);
+                }
+                Err(error) => return Err(error),
+            };
+
+            let mut tokens = line.split_whitespace();
+
+            if tokens.next() == Some("bestmove") {
+                return Ok(tokens.next().unwrap_or_default().to_owned());
+            }
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // The engine may already have exited, in which case writing fails
+        let _ = self.stdin.write_all(b"quit\n");
+        let _ = self.stdin.flush();
+
+        let deadline = Instant::now() + QUIT_GRACE_PERIOD;
+
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Parses an opening book with one position per line, written either as a FEN or as an
+/// EPD. Blank lines and lines starting with `#` are skipped.
+///
+/// EPD operations are dropped and the move counters are reset, so every position is
+/// returned as a FEN.
+pub fn parse_opening_book(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+
+            let has_counters = fields.len() >= 6
+                && fields[4].parse::<u32>().is_ok()
+                && fields[5].parse::<u32>().is_ok();
+
+            if has_counters {
+                fields[..6].join(" ")
+            } else {
+                format!("{} 0 1", fields[..fields.len().min(4)].join(" "))
+            }
         })
+        .collect()
+}
+
+/// Builds the UCI `position` command for a game started from `fen`.
+pub fn position_command(fen: &str, moves: &[Move]) -> String {
+    let mut command = format!("position fen {fen}");
+
+    if !moves.is_empty() {
+        command.push_str(" moves");
+
+        for mv in moves {
+            command.push_str(&format!(" {mv}"));
+        }
+    }
+
+    command.push('\n');
+    command
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            Self::WhiteWins => Some(Color::White),
+            Self::BlackWins => Some(Color::Black),
+            Self::Draw => None,
+        }
     }
 }
 
 pub struct GameLog {
-    pub result: Color,
+    pub result: GameResult,
+    /// FEN of the starting position
+    pub opening: String,
     pub moves: Vec<Move>,
+    /// FEN of the position after each move, so a game can be replayed up to any point
+    pub fens: Vec<String>,
 }
 
+impl GameLog {
+    /// A game from `opening` with no moves played.
+    pub fn new(opening: &str, result: GameResult) -> Self {
+        Self {
+            result,
+            opening: opening.to_owned(),
+            moves: Vec::new(),
+            fens: Vec::new(),
+        }
+    }
+
+    /// The result as written at the end of a PGN game.
+    pub fn pgn_result_string(&self) -> &str {
+        match self.result {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+}
+
+/// A match between two engines. Wins, losses and draws are from `engine_1`'s perspective.
 pub struct Session {
     pub wins: u32,
     pub losses: u32,
@@ -39,4 +268,265 @@ pub struct Session {
     pub engine_1: Engine,
     pub engine_2: Engine,
     pub games: Vec<GameLog>,
+    /// Starting positions as FENs, each played twice with colors reversed. Games start
+    /// from the standard position if this is empty.
+    pub openings: Vec<String>,
+}
+
+impl Session {
+    pub fn new(engine_1: Engine, engine_2: Engine) -> Self {
+        Self {
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            engine_1,
+            engine_2,
+            games: Vec::new(),
+            openings: Vec::new(),
+        }
+    }
+
+    /// Plays up to `max_games` games, stopping early once the SPRT reaches a verdict.
+    ///
+    /// `play_game` is given the engines playing White and Black, and the FEN to start
+    /// from. The engines swap colors after every game, with `engine_1` playing White first,
+    /// and each opening is used for two games in a row.
+    pub fn run_match(
+        &mut self,
+        max_games: u32,
+        sprt: Option<Sprt>,
+        mut play_game: impl FnMut(&mut Engine, &mut Engine, &str) -> GameLog,
+    ) -> Option<SprtVerdict> {
+        for game in 0..max_games {
+            let opening = match self.openings.len() {
+                0 => START_FEN,
+                len => &self.openings[(game / 2) as usize % len],
+            };
+
+            let (log, engine_1_color) = if game.is_multiple_of(2) {
+                (
+                    play_game(&mut self.engine_1, &mut self.engine_2, opening),
+                    Color::White,
+                )
+            } else {
+                (
+                    play_game(&mut self.engine_2, &mut self.engine_1, opening),
+                    Color::Black,
+                )
+            };
+
+            self.record_game(log, engine_1_color);
+
+            if let Some(verdict) = sprt.and_then(|sprt| self.sprt_verdict(&sprt)) {
+                return Some(verdict);
+            }
+        }
+
+        None
+    }
+
+    /// Tallies a finished game, in which `engine_1` played `engine_1_color`.
+    pub fn record_game(&mut self, game: GameLog, engine_1_color: Color) {
+        match game.result.winner() {
+            Some(color) if color == engine_1_color => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+
+        self.games.push(game);
+    }
+
+    /// Fraction of the available points `engine_1` scored.
+    pub fn score(&self) -> f64 {
+        stats::score(self.wins, self.losses, self.draws)
+    }
+
+    /// Estimated Elo difference of `engine_1` over `engine_2`.
+    pub fn elo_difference(&self) -> f64 {
+        stats::elo_difference(self.score())
+    }
+
+    /// Half the width of the 95% confidence interval of [`Session::elo_difference`].
+    pub fn elo_error_margin(&self) -> f64 {
+        stats::elo_error_margin(self.wins, self.losses, self.draws)
+    }
+
+    pub fn sprt_verdict(&self, sprt: &Sprt) -> Option<SprtVerdict> {
+        sprt.verdict(self.wins, self.losses, self.draws)
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    /// A session between two processes which never play, for tests which decide the
+    /// games themselves
+    fn idle_session() -> Session {
+        Session::new(
+            Engine::new("engine1".to_owned(), Path::new("cat")).unwrap(),
+            Engine::new("engine2".to_owned(), Path::new("cat")).unwrap(),
+        )
+    }
+
+    /// Runs a match where `engine_1` wins, draws or loses following a repeating pattern
+    fn run_pattern(pattern: [Option<bool>; 4]) -> (Option<SprtVerdict>, Session) {
+        let mut session = idle_session();
+        let sprt = Sprt::new(0.0, 20.0, 0.05, 0.05);
+
+        let mut game = 0;
+        let verdict = session.run_match(1000, Some(sprt), |white, _, _| {
+            let engine_1_wins = pattern[game % 4];
+            game += 1;
+
+            let result = match engine_1_wins {
+                Some(engine_1_wins) if engine_1_wins == (white.id == "engine1") => {
+                    GameResult::WhiteWins
+                }
+                Some(_) => GameResult::BlackWins,
+                None => GameResult::Draw,
+            };
+
+            GameLog::new(START_FEN, result)
+        });
+
+        (verdict, session)
+    }
+
+    #[test]
+    fn sprt_accepts_h1() {
+        let (verdict, session) = run_pattern([Some(true), Some(true), None, Some(false)]);
+
+        assert_eq!(verdict, Some(SprtVerdict::AcceptH1));
+        assert_eq!(session.games.len(), 154);
+        assert_eq!((session.wins, session.losses, session.draws), (78, 38, 38));
+    }
+
+    #[test]
+    fn sprt_accepts_h0() {
+        let (verdict, session) = run_pattern([Some(false), None, Some(true), Some(false)]);
+
+        assert_eq!(verdict, Some(SprtVerdict::AcceptH0));
+        assert_eq!(session.games.len(), 125);
+    }
+
+    #[test]
+    fn pgn_result_string() {
+        let results = [
+            (GameResult::WhiteWins, "1-0", Some(Color::White)),
+            (GameResult::BlackWins, "0-1", Some(Color::Black)),
+            (GameResult::Draw, "1/2-1/2", None),
+        ];
+
+        for (result, pgn, winner) in results {
+            let game = GameLog::new(START_FEN, result);
+
+            assert_eq!(game.pgn_result_string(), pgn);
+            assert_eq!(result.winner(), winner);
+        }
+    }
+
+    #[test]
+    fn openings_played_from_both_sides() {
+        let mut session = idle_session();
+        session.openings = vec!["opening 1".to_owned(), "opening 2".to_owned()];
+
+        let mut played = Vec::new();
+
+        session.run_match(6, None, |white, black, opening| {
+            played.push((white.id.clone(), black.id.clone(), opening.to_owned()));
+
+            GameLog::new(opening, GameResult::Draw)
+        });
+
+        let expected = [
+            ("engine1", "engine2", "opening 1"),
+            ("engine2", "engine1", "opening 1"),
+            ("engine1", "engine2", "opening 2"),
+            ("engine2", "engine1", "opening 2"),
+            ("engine1", "engine2", "opening 1"),
+            ("engine2", "engine1", "opening 1"),
+        ]
+        .map(|(white, black, opening)| (white.to_owned(), black.to_owned(), opening.to_owned()));
+
+        assert_eq!(played, expected);
+    }
+
+    #[test]
+    fn parse_opening_book() {
+        let book = "\
+# Italian
+r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3
+
+rnbqkb1r/pppppppp/5n2/8/3P4/8/PPP1PPPP/RNBQKBNR w KQkq - bm c4; id \"Indian\";
+";
+
+        assert_eq!(
+            super::parse_opening_book(book),
+            [
+                "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+                "rnbqkb1r/pppppppp/5n2/8/3P4/8/PPP1PPPP/RNBQKBNR w KQkq - 0 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn position_command() {
+        let moves = ["e2e4", "e7e5"].map(|mv| Move::try_from(mv).unwrap());
+
+        assert_eq!(
+            super::position_command(START_FEN, &[]),
+            format!("position fen {START_FEN}\n")
+        );
+        assert_eq!(
+            super::position_command(START_FEN, &moves),
+            format!("position fen {START_FEN} moves e2e4 e7e5\n")
+        );
+    }
+
+    #[cfg(unix)]
+    fn process_exists(pid: u32) -> bool {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap()
+            .success()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn drop_kills_unresponsive_engine() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "exec sleep 60"]);
+
+        let engine = Engine::from_command("sleeper".to_owned(), command).unwrap();
+        let pid = engine.process.id();
+
+        assert!(process_exists(pid));
+
+        drop(engine);
+
+        assert!(!process_exists(pid));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn drop_sends_quit() {
+        // Exits as soon as it reads a line, well within the grace period
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            "read line; [ \"$line\" = quit ] && exit 0; exec sleep 60",
+        ]);
+
+        let engine = Engine::from_command("quitter".to_owned(), command).unwrap();
+        let pid = engine.process.id();
+
+        let start = Instant::now();
+        drop(engine);
+
+        assert!(start.elapsed() < QUIT_GRACE_PERIOD);
+        assert!(!process_exists(pid));
+    }
 }
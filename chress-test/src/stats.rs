@@ -0,0 +1,189 @@
+//! Match statistics, for judging whether one engine is stronger than another.
+
+/// Fraction of the available points scored, counting draws as half a point.
+pub fn score(wins: u32, losses: u32, draws: u32) -> f64 {
+    let games = wins + losses + draws;
+
+    if games == 0 {
+        return 0.5;
+    }
+
+    (wins as f64 + draws as f64 / 2.0) / games as f64
+}
+
+/// Elo difference which would give the expected score, using the logistic formula.
+///
+/// Returns an infinite value for scores of 0 or 1.
+pub fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Half the width of the 95% confidence interval of the Elo difference, so that the true
+/// difference is likely to be within `elo ± margin`.
+///
+/// Returns an infinite margin without any games, or when every game was won or every game
+/// was lost, as the Elo difference is unbounded then.
+pub fn elo_error_margin(wins: u32, losses: u32, draws: u32) -> f64 {
+    let games = (wins + losses + draws) as f64;
+    let score = score(wins, losses, draws);
+
+    if games == 0.0 || score == 0.0 || score == 1.0 {
+        return f64::INFINITY;
+    }
+
+    // Variance of the result of a single game
+    let variance = (wins as f64 * (1.0 - score).powi(2)
+        + losses as f64 * score.powi(2)
+        + draws as f64 * (0.5 - score).powi(2))
+        / games;
+
+    let deviation = (variance / games).sqrt();
+
+    // Keep the bounds within (0, 1) so that their Elo differences stay finite
+    let clamp = |score: f64| score.clamp(f64::EPSILON, 1.0 - f64::EPSILON);
+
+    let upper = elo_difference(clamp(score + 1.96 * deviation));
+    let lower = elo_difference(clamp(score - 1.96 * deviation));
+
+    (upper - lower) / 2.0
+}
+
+/// Expected score of an engine which is `elo` stronger than its opponent.
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtVerdict {
+    /// The Elo difference is likely at most `elo0`
+    AcceptH0,
+    /// The Elo difference is likely at least `elo1`
+    AcceptH1,
+}
+
+/// Sequential probability ratio test, deciding between the hypotheses that the Elo
+/// difference is `elo0` (H0) or `elo1` (H1) with false positive rate `alpha` and false
+/// negative rate `beta`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        }
+    }
+
+    /// H0 is accepted once the log-likelihood ratio falls to this value.
+    pub fn lower_bound(&self) -> f64 {
+        (self.beta / (1.0 - self.alpha)).ln()
+    }
+
+    /// H1 is accepted once the log-likelihood ratio reaches this value.
+    pub fn upper_bound(&self) -> f64 {
+        ((1.0 - self.beta) / self.alpha).ln()
+    }
+
+    /// Log-likelihood ratio of H1 over H0, using the normal approximation of the
+    /// generalized SPRT.
+    ///
+    /// Until the results vary, their variance is unknown and the ratio is 0.
+    pub fn llr(&self, wins: u32, losses: u32, draws: u32) -> f64 {
+        let games = (wins + losses + draws) as f64;
+        let score = score(wins, losses, draws);
+
+        let variance = (wins as f64 * (1.0 - score).powi(2)
+            + losses as f64 * score.powi(2)
+            + draws as f64 * (0.5 - score).powi(2))
+            / games;
+
+        if games == 0.0 || variance == 0.0 {
+            return 0.0;
+        }
+
+        let score0 = expected_score(self.elo0);
+        let score1 = expected_score(self.elo1);
+
+        games * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Returns a verdict once the log-likelihood ratio crosses either bound.
+    pub fn verdict(&self, wins: u32, losses: u32, draws: u32) -> Option<SprtVerdict> {
+        let llr = self.llr(wins, losses, draws);
+
+        if llr >= self.upper_bound() {
+            Some(SprtVerdict::AcceptH1)
+        } else if llr <= self.lower_bound() {
+            Some(SprtVerdict::AcceptH0)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 0.01, "{a} != {b}");
+    }
+
+    #[test]
+    fn score_counts_draws_as_half() {
+        assert_close(score(6, 2, 2), 0.7);
+        assert_close(score(0, 0, 10), 0.5);
+        assert_close(score(0, 0, 0), 0.5);
+    }
+
+    #[test]
+    fn elo_difference_known_tallies() {
+        // 400 * log10(3)
+        assert_close(elo_difference(score(3, 1, 0)), 190.85);
+        // 400 * log10(7 / 3)
+        assert_close(elo_difference(score(6, 2, 2)), 147.19);
+        assert_close(elo_difference(score(1, 3, 0)), -190.85);
+        assert_close(elo_difference(score(5, 5, 10)), 0.0);
+    }
+
+    #[test]
+    fn elo_error_margin_shrinks_with_more_games() {
+        let small = elo_error_margin(6, 4, 10);
+        let large = elo_error_margin(60, 40, 100);
+
+        assert!(large < small);
+
+        // Score 0.55 with a deviation of sqrt(0.1225 / 200)
+        assert_close(large, 34.16);
+    }
+
+    #[test]
+    fn elo_error_margin_edge_cases() {
+        assert_eq!(elo_error_margin(0, 0, 0), f64::INFINITY);
+        assert_eq!(elo_error_margin(10, 0, 0), f64::INFINITY);
+        assert_eq!(elo_error_margin(0, 10, 0), f64::INFINITY);
+        assert_eq!(elo_error_margin(0, 0, 10), 0.0);
+
+        // The upper bound of the interval is above a score of 1 and gets clamped
+        let margin = elo_error_margin(9, 1, 0);
+        assert!(margin.is_finite() && margin > 0.0, "{margin}");
+    }
+
+    #[test]
+    fn sprt_bounds() {
+        let sprt = Sprt::new(0.0, 20.0, 0.05, 0.05);
+
+        assert_close(sprt.lower_bound(), -2.944);
+        assert_close(sprt.upper_bound(), 2.944);
+        assert_eq!(sprt.llr(0, 0, 0), 0.0);
+        assert_eq!(sprt.llr(10, 0, 0), 0.0);
+    }
+}
@@ -0,0 +1,24 @@
+use chress::{board::Board, move_gen::MoveGen};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn make_unmake(c: &mut Criterion) {
+    let move_gen = MoveGen::new();
+    let mut board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+    let mut moves = Vec::new();
+    move_gen.legal_moves(&board, &mut moves);
+
+    c.bench_function("make_unmake_kiwipete", |b| {
+        b.iter(|| {
+            for &mv in &moves {
+                let move_data = board.make_move(black_box(mv)).unwrap();
+                board.unmake_move(move_data).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, make_unmake);
+criterion_main!(benches);
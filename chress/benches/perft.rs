@@ -0,0 +1,16 @@
+use chress::{board::Board, debug::perft, move_gen::MoveGen};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn perft_kiwipete(c: &mut Criterion) {
+    let move_gen = MoveGen::new();
+    let board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+    c.bench_function("perft_kiwipete_3", |b| {
+        b.iter(|| perft(black_box(board), &move_gen, 3))
+    });
+}
+
+criterion_group!(benches, perft_kiwipete);
+criterion_main!(benches);
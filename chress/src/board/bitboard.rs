@@ -20,6 +20,68 @@ impl Bitboard {
     pub const EMPTY: Bitboard = Bitboard(0);
     pub const UNIVERSE: Bitboard = Bitboard(u64::MAX);
 
+    pub const FILE_A: Bitboard = Bitboard(0x0101010101010101);
+    pub const FILE_B: Bitboard = Bitboard(0x0202020202020202);
+    pub const FILE_C: Bitboard = Bitboard(0x0404040404040404);
+    pub const FILE_D: Bitboard = Bitboard(0x0808080808080808);
+    pub const FILE_E: Bitboard = Bitboard(0x1010101010101010);
+    pub const FILE_F: Bitboard = Bitboard(0x2020202020202020);
+    pub const FILE_G: Bitboard = Bitboard(0x4040404040404040);
+    pub const FILE_H: Bitboard = Bitboard(0x8080808080808080);
+
+    pub const RANK_1: Bitboard = Bitboard(0x00000000000000FF);
+    pub const RANK_2: Bitboard = Bitboard(0x000000000000FF00);
+    pub const RANK_3: Bitboard = Bitboard(0x0000000000FF0000);
+    pub const RANK_4: Bitboard = Bitboard(0x00000000FF000000);
+    pub const RANK_5: Bitboard = Bitboard(0x000000FF00000000);
+    pub const RANK_6: Bitboard = Bitboard(0x0000FF0000000000);
+    pub const RANK_7: Bitboard = Bitboard(0x00FF000000000000);
+    pub const RANK_8: Bitboard = Bitboard(0xFF00000000000000);
+
+    pub const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA55AA55AA55AA);
+    pub const DARK_SQUARES: Bitboard = Bitboard(0xAA55AA55AA55AA55);
+
+    /// Masks of the a to h files, indexed by `Square::file`.
+    pub const FILES: [Bitboard; 8] = [
+        Self::FILE_A,
+        Self::FILE_B,
+        Self::FILE_C,
+        Self::FILE_D,
+        Self::FILE_E,
+        Self::FILE_F,
+        Self::FILE_G,
+        Self::FILE_H,
+    ];
+
+    /// Masks of the first to eighth ranks, indexed by `Square::rank`.
+    pub const RANKS: [Bitboard; 8] = [
+        Self::RANK_1,
+        Self::RANK_2,
+        Self::RANK_3,
+        Self::RANK_4,
+        Self::RANK_5,
+        Self::RANK_6,
+        Self::RANK_7,
+        Self::RANK_8,
+    ];
+
+    #[inline]
+    pub const fn file_mask(file: u8) -> Bitboard {
+        Self::FILES[file as usize]
+    }
+
+    #[inline]
+    pub const fn rank_mask(rank: u8) -> Bitboard {
+        Self::RANKS[rank as usize]
+    }
+
+    /// The files either side of `file`, not including `file` itself.
+    pub const fn adjacent_files(file: u8) -> Bitboard {
+        let mask = Self::FILES[file as usize].0;
+
+        Bitboard(((mask << 1) & !Self::FILE_A.0) | ((mask >> 1) & !Self::FILE_H.0))
+    }
+
     pub fn subsets(&self) -> Vec<Bitboard> {
         let mut subsets = vec![];
 
@@ -46,14 +108,16 @@ impl Bitboard {
     }
 
     /// Pops the least significant bit, returning its index in the bitboard.
-    pub fn pop_lsb(&mut self) -> u32 {
+    #[inline]
+    pub const fn pop_lsb(&mut self) -> u32 {
         let i = self.0.trailing_zeros();
         self.0 &= self.0 - 1;
         i
     }
 
-    pub fn is_empty(&self) -> bool {
-        *self == Bitboard::EMPTY
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
     }
 }
 
@@ -94,6 +158,7 @@ impl Display for Bitboard {
 
 impl Mul<bool> for Bitboard {
     type Output = Bitboard;
+    #[inline]
     fn mul(self, rhs: bool) -> Self::Output {
         Bitboard(self.0 * rhs as u64)
     }
@@ -101,6 +166,7 @@ impl Mul<bool> for Bitboard {
 
 impl Not for Bitboard {
     type Output = Bitboard;
+    #[inline]
     fn not(self) -> Self::Output {
         Bitboard(!self.0)
     }
@@ -108,6 +174,7 @@ impl Not for Bitboard {
 
 impl Shl<Self> for Bitboard {
     type Output = Bitboard;
+    #[inline]
     fn shl(self, rhs: Self) -> Self::Output {
         Bitboard(self.0 << rhs.0)
     }
@@ -115,18 +182,21 @@ impl Shl<Self> for Bitboard {
 
 impl Shr<Self> for Bitboard {
     type Output = Bitboard;
+    #[inline]
     fn shr(self, rhs: Self) -> Self::Output {
         Bitboard(self.0 >> rhs.0)
     }
 }
 
 impl ShlAssign<Self> for Bitboard {
+    #[inline]
     fn shl_assign(&mut self, rhs: Self) {
         self.0 <<= rhs.0;
     }
 }
 
 impl ShrAssign<Self> for Bitboard {
+    #[inline]
     fn shr_assign(&mut self, rhs: Self) {
         self.0 >>= rhs.0;
     }
@@ -136,6 +206,7 @@ macro_rules! impl_bit_ops {
     ($op:ident, $fn:ident, $ex:tt) => {
         impl $op for Bitboard {
             type Output = Bitboard;
+            #[inline]
             fn $fn(self, rhs: Self) -> Self::Output {
                 Bitboard(self.0 $ex rhs.0)
             }
@@ -146,6 +217,7 @@ macro_rules! impl_bit_ops {
 macro_rules! impl_bit_ops_assign {
     ($op:ident, $fn:ident, $ex:tt) => {
         impl $op for Bitboard {
+            #[inline]
             fn $fn(&mut self, rhs: Self) {
                 self.0 $ex rhs.0;
             }
@@ -165,12 +237,14 @@ macro_rules! impl_shift {
     ($t:ty) => {
         impl Shl<$t> for Bitboard {
             type Output = Bitboard;
+            #[inline]
             fn shl(self, rhs: $t) -> Self::Output {
                 Bitboard(self.0 << rhs)
             }
         }
 
         impl ShlAssign<$t> for Bitboard {
+            #[inline]
             fn shl_assign(&mut self, rhs: $t) {
                 self.0 <<= rhs;
             }
@@ -178,12 +252,14 @@ macro_rules! impl_shift {
 
         impl Shr<$t> for Bitboard {
             type Output = Bitboard;
+            #[inline]
             fn shr(self, rhs: $t) -> Self::Output {
                 Bitboard(self.0 >> rhs)
             }
         }
 
         impl ShrAssign<$t> for Bitboard {
+            #[inline]
             fn shr_assign(&mut self, rhs: $t) {
                 self.0 >>= rhs;
             }
@@ -203,3 +279,92 @@ impl_shift!(i32);
 impl_shift!(i64);
 impl_shift!(i128);
 impl_shift!(isize);
+
+#[cfg(test)]
+mod bitboard_tests {
+    use super::*;
+
+    #[test]
+    fn named_masks() {
+        let a_file = [
+            Square::A1,
+            Square::A2,
+            Square::A3,
+            Square::A4,
+            Square::A5,
+            Square::A6,
+            Square::A7,
+            Square::A8,
+        ];
+        let first_rank = [
+            Square::A1,
+            Square::B1,
+            Square::C1,
+            Square::D1,
+            Square::E1,
+            Square::F1,
+            Square::G1,
+            Square::H1,
+        ];
+
+        assert_eq!(
+            Bitboard::FILE_A,
+            a_file
+                .iter()
+                .fold(Bitboard::EMPTY, |mask, square| mask | square.bitboard())
+        );
+        assert_eq!(
+            Bitboard::RANK_1,
+            first_rank
+                .iter()
+                .fold(Bitboard::EMPTY, |mask, square| mask | square.bitboard())
+        );
+    }
+
+    #[test]
+    fn square_colors_match_squares() {
+        for square in Square::ALL {
+            let light = !(Bitboard::LIGHT_SQUARES & square.bitboard()).is_empty();
+            let dark = !(Bitboard::DARK_SQUARES & square.bitboard()).is_empty();
+
+            assert_eq!(light, square.is_light(), "{square}");
+            assert_eq!(dark, !square.is_light(), "{square}");
+        }
+    }
+
+    #[test]
+    fn ranks_match_squares() {
+        for square in Square::ALL {
+            for rank in 0..8 {
+                assert_eq!(
+                    !(Bitboard::rank_mask(rank) & square.bitboard()).is_empty(),
+                    square.rank() == rank,
+                    "{square}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn adjacent_files() {
+        assert_eq!(Bitboard::adjacent_files(0), Bitboard::FILE_B);
+        assert_eq!(
+            Bitboard::adjacent_files(3),
+            Bitboard::FILE_C | Bitboard::FILE_E
+        );
+        assert_eq!(Bitboard::adjacent_files(7), Bitboard::FILE_G);
+    }
+
+    #[test]
+    fn files_match_squares() {
+        for square in Square::ALL {
+            for (file, mask) in Bitboard::FILES.iter().enumerate() {
+                assert_eq!(
+                    !(*mask & square.bitboard()).is_empty(),
+                    square.file() as usize == file,
+                    "{square}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::{cell::RefCell, ops::Deref};
+
+use crate::{
+    board::{
+        r#move::{Move, MoveData},
+        san::ParseSanError,
+        Board, GameResult, MakeMoveError, ParseFenError, UnmakeMoveError,
+    },
+    move_gen::MoveGen,
+};
+
+/// A `Board` paired with the `MoveGen` it's played with, so that methods which need move
+/// generation don't have to be passed one.
+///
+/// Dereferences to the inner `Board` for everything else. Moves can only be made through
+/// `make_move_checked`, so the board always stays legal.
+///
+/// The legal moves of the current position are cached, so repeated queries (for example
+/// from a GUI) don't regenerate them. The hashes of earlier positions are kept as well, to
+/// detect repetitions.
+#[derive(Debug, Clone)]
+pub struct BoundBoard<'a> {
+    board: Board,
+    move_gen: &'a MoveGen,
+    /// Legal moves of the position with the given Zobrist hash
+    legal_moves: RefCell<Option<(u64, Vec<Move>)>>,
+    /// Hashes of the positions before each move made, oldest first
+    history: Vec<u64>,
+}
+
+impl<'a> BoundBoard<'a> {
+    pub fn new(board: Board, move_gen: &'a MoveGen) -> Self {
+        Self {
+            board,
+            move_gen,
+            legal_moves: RefCell::new(None),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn from_fen(fen: &str, move_gen: &'a MoveGen) -> Result<Self, ParseFenError> {
+        Ok(Self::new(Board::from_fen(fen, move_gen)?, move_gen))
+    }
+
+    pub fn board(&self) -> Board {
+        self.board
+    }
+
+    pub fn move_gen(&self) -> &'a MoveGen {
+        self.move_gen
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut cache = self.legal_moves.borrow_mut();
+
+        match &*cache {
+            Some((hash, moves)) if *hash == self.board.hash => moves.clone(),
+            _ => {
+                let moves = self.move_gen.legal_moves_vec(&self.board);
+                *cache = Some((self.board.hash, moves.clone()));
+                moves
+            }
+        }
+    }
+
+    pub fn make_move_checked(&mut self, r#move: Move) -> Result<MoveData, MakeMoveError> {
+        let hash = self.board.hash;
+        let move_data = self.board.make_move_checked(r#move, self.move_gen)?;
+
+        self.history.push(hash);
+        self.legal_moves.take();
+
+        Ok(move_data)
+    }
+
+    pub fn unmake_move(&mut self, move_data: MoveData) -> Result<(), UnmakeMoveError> {
+        self.board.unmake_move(move_data)?;
+
+        self.history.pop();
+        self.legal_moves.take();
+
+        Ok(())
+    }
+
+    /// Checks whether the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        self.board.is_in_check(self.move_gen)
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.board.is_forced(self.move_gen)
+    }
+
+    pub fn move_to_san(&self, mv: Move) -> String {
+        self.board.move_to_san(mv, self.move_gen)
+    }
+
+    pub fn parse_san(&self, san: &str) -> Result<Move, ParseSanError> {
+        self.board.parse_san(san, self.move_gen)
+    }
+
+    pub fn see(&self, mv: Move) -> i32 {
+        self.board.see(self.move_gen, mv)
+    }
+
+    /// Checks whether the current position occurred before. Enough for the search to
+    /// score it as a draw.
+    ///
+    /// The whole history is kept so that moves can be unmade, but only positions since
+    /// the last capture or pawn move are scanned. Castling rights are part of the hash, so
+    /// positions from before losing a right never match.
+    pub fn is_repetition(&self) -> bool {
+        self.board.repetitions(&self.history) >= 1
+    }
+
+    /// Checks whether the current position occurred at least three times.
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.board.repetitions(&self.history) >= 2
+    }
+
+    /// Like [`Board::game_over`], but also detects threefold repetition.
+    pub fn game_over(&self) -> Option<GameResult> {
+        self.board.game_over(self.move_gen).or_else(|| {
+            self.is_threefold_repetition()
+                .then_some(GameResult::DrawByRepetition)
+        })
+    }
+}
+
+impl Deref for BoundBoard<'_> {
+    type Target = Board;
+
+    fn deref(&self) -> &Self::Target {
+        &self.board
+    }
+}
+
+#[cfg(test)]
+mod bound_tests {
+    use super::*;
+    use crate::board::{color::Color, START_FEN};
+
+    #[test]
+    fn forwards_to_board_and_move_gen() {
+        let move_gen = MoveGen::new();
+        let mut board = BoundBoard::from_fen(START_FEN, &move_gen).unwrap();
+
+        assert_eq!(board.legal_moves().len(), 20);
+        assert!(!board.in_check());
+        assert!(!board.is_forced());
+
+        let e4 = board.parse_san("e4").unwrap();
+        assert_eq!(board.move_to_san(e4), "e4");
+
+        let move_data = board.make_move_checked(e4).unwrap();
+        assert_eq!(board.active_color, Color::Black);
+        assert_eq!(board.legal_moves().len(), 20);
+
+        board.unmake_move(move_data).unwrap();
+        assert_eq!(board.board(), Board::default());
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        let move_gen = MoveGen::new();
+        let mut board = BoundBoard::new(Board::default(), &move_gen);
+
+        assert!(board
+            .make_move_checked(Move::try_from("e2e5").unwrap())
+            .is_err());
+        assert_eq!(board.board(), Board::default());
+    }
+
+    #[test]
+    fn in_check_and_forced() {
+        let move_gen = MoveGen::new();
+
+        // The black king is checked by the a1 rook and can only escape to b8
+        let board = BoundBoard::from_fen("k7/2R5/8/8/8/8/8/R3K3 b - - 0 1", &move_gen).unwrap();
+
+        assert!(board.in_check());
+        assert!(board.is_forced());
+        assert_eq!(board.legal_moves(), [Move::try_from("a8b8").unwrap()]);
+    }
+
+    #[test]
+    fn caches_legal_moves() {
+        let move_gen = MoveGen::new();
+        let mut board = BoundBoard::new(Board::default(), &move_gen);
+
+        assert!(board.legal_moves.borrow().is_none());
+
+        let moves = board.legal_moves();
+        assert_eq!(
+            *board.legal_moves.borrow(),
+            Some((board.hash, moves.clone()))
+        );
+
+        // A second query is served from the cache, without generating moves again
+        assert_eq!(board.legal_moves(), moves);
+        *board.legal_moves.borrow_mut() = Some((board.hash, Vec::new()));
+        assert!(board.legal_moves().is_empty());
+
+        // Making a move invalidates it
+        board
+            .make_move_checked(Move::try_from("e2e4").unwrap())
+            .unwrap();
+        assert!(board.legal_moves.borrow().is_none());
+
+        let moves = board.legal_moves();
+        assert_eq!(*board.legal_moves.borrow(), Some((board.hash, moves)));
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let move_gen = MoveGen::new();
+        let mut board = BoundBoard::new(Board::default(), &move_gen);
+
+        let knight_dance = ["g1f3", "g8f6", "f3g1", "f6g8"].map(|mv| Move::try_from(mv).unwrap());
+
+        assert!(!board.is_repetition());
+
+        for mv in knight_dance {
+            board.make_move_checked(mv).unwrap();
+        }
+
+        assert!(board.is_repetition());
+        assert!(!board.is_threefold_repetition());
+        assert_eq!(board.game_over(), None);
+
+        let mut history = Vec::new();
+
+        for mv in knight_dance {
+            history.push(board.make_move_checked(mv).unwrap());
+        }
+
+        assert!(board.is_threefold_repetition());
+        assert_eq!(board.game_over(), Some(GameResult::DrawByRepetition));
+
+        // Unmaking moves takes their positions out of the history
+        while let Some(move_data) = history.pop() {
+            board.unmake_move(move_data).unwrap();
+        }
+
+        assert!(board.is_repetition());
+        assert!(!board.is_threefold_repetition());
+    }
+
+    #[test]
+    fn lost_castling_rights_break_repetitions() {
+        let move_gen = MoveGen::new();
+        let mut board =
+            BoundBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &move_gen).unwrap();
+
+        // The kings return to the same squares, but without their castling rights
+        for mv in ["e1e2", "e8e7", "e2e1", "e7e8"] {
+            board
+                .make_move_checked(Move::try_from(mv).unwrap())
+                .unwrap();
+        }
+
+        assert!(!board.is_repetition());
+
+        for mv in ["e1e2", "e8e7", "e2e1", "e7e8"] {
+            board
+                .make_move_checked(Move::try_from(mv).unwrap())
+                .unwrap();
+        }
+
+        assert!(board.is_repetition());
+        assert!(!board.is_threefold_repetition());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    board::{bitboard::Bitboard, piece::Piece, r#move::Move, square::Square, Board},
+    build::lines::{BETWEEN, LINE},
+    move_gen::{knight_attacks, pawn_attacks, MoveGen},
+};
+
+/// Information about how the side to move can give check, computed once per position so
+/// that `Board::gives_check` doesn't have to make each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckInfo {
+    /// The king of the side not to move
+    pub king_square: Square,
+    /// Squares from which each piece would attack the king, indexed by `Piece`
+    pub check_squares: [Bitboard; 6],
+    /// Pieces of the side to move which are the only piece between one of its sliders and
+    /// the king, so moving them off the line gives discovered check
+    pub discoverers: Bitboard,
+}
+
+impl Board {
+    /// Computes the `CheckInfo` of the position, or `None` if the side not to move has no
+    /// king to check.
+    pub fn check_info(&self, move_gen: &MoveGen) -> Option<CheckInfo> {
+        let us = self.us();
+        let them = self.them();
+
+        let Ok(king_square) = Square::try_from(self.bitboard(Piece::King, them)) else {
+            return None;
+        };
+        let occupied = self.occupied();
+
+        let bishop_checks = move_gen.bishop_attacks(king_square, occupied);
+        let rook_checks = move_gen.rook_attacks(king_square, occupied);
+
+        // Kings can't give check, so their check squares are left empty
+        let mut check_squares = [Bitboard::EMPTY; 6];
+        check_squares[Piece::Knight as usize] = knight_attacks(king_square);
+        check_squares[Piece::Bishop as usize] = bishop_checks;
+        check_squares[Piece::Rook as usize] = rook_checks;
+        check_squares[Piece::Queen as usize] = bishop_checks | rook_checks;
+        check_squares[Piece::Pawn as usize] = pawn_attacks(king_square, them);
+
+        let queens = self.bitboard(Piece::Queen, us);
+        let mut snipers = (move_gen.rook_attacks(king_square, Bitboard::EMPTY)
+            & (self.bitboard(Piece::Rook, us) | queens))
+            | (move_gen.bishop_attacks(king_square, Bitboard::EMPTY)
+                & (self.bitboard(Piece::Bishop, us) | queens));
+
+        let mut discoverers = Bitboard::EMPTY;
+
+        for _ in 0..snipers.0.count_ones() {
+            let sniper = snipers.pop_lsb() as usize;
+            let blockers = BETWEEN[sniper][king_square as usize] & occupied;
+
+            if blockers.0.count_ones() == 1 {
+                discoverers |= blockers & self.color_pieces(us);
+            }
+        }
+
+        Some(CheckInfo {
+            king_square,
+            check_squares,
+            discoverers,
+        })
+    }
+
+    /// Checks whether a legal move gives check, using `info` from `Board::check_info`
+    /// for this position.
+    ///
+    /// Promotions, castling and en passant are made on a copy of the board instead.
+    pub fn gives_check(&self, move_gen: &MoveGen, info: &CheckInfo, mv: Move) -> bool {
+        let from = mv.from();
+        let to = mv.to();
+
+        let Some(piece) = self.piece_at(from) else {
+            return false;
+        };
+
+        let is_castling = piece == Piece::King && from.file().abs_diff(to.file()) == 2;
+        let is_en_passant = piece == Piece::Pawn && self.en_passant_square() == Some(to);
+
+        if mv.promotion().is_some() || is_castling || is_en_passant {
+            let mut board = *self;
+
+            if board.make_move(mv).is_err() {
+                return false;
+            }
+
+            return move_gen.square_attacked_by(&board, info.king_square, self.us());
+        }
+
+        if !(info.check_squares[piece as usize] & to.bitboard()).is_empty() {
+            return true;
+        }
+
+        !(info.discoverers & from.bitboard()).is_empty()
+            && (LINE[from as usize][info.king_square as usize] & to.bitboard()).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod check_tests {
+    use crate::board::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn gives_check_matches_make_move() {
+        let move_gen = MoveGen::new();
+
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            // Discovered checks by a knight, a pawn and the king
+            "3k4/8/3N4/8/3R4/8/3P4/B1K2N2 w - - 0 1",
+            "4k3/8/8/4P3/8/8/4R3/K7 w - - 0 1",
+            "7k/8/8/8/8/8/1K6/B7 w - - 0 1",
+            // Castling, en passant and promotion checks
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+            "8/8/8/1k1pP2Q/8/8/8/4K3 w - d6 0 1",
+            "8/8/8/k1pP3R/8/8/8/4K3 w - c6 0 1",
+            "3k4/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        ];
+
+        for fen in fens {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+            let info = board.check_info(&move_gen).unwrap();
+
+            let mut moves = Vec::new();
+            move_gen.legal_moves(&board, &mut moves);
+
+            for mv in moves {
+                let mut after = board;
+                after.make_move(mv).unwrap();
+
+                let expected = move_gen.square_attacked_by(
+                    &after,
+                    after.king_square(board.them()),
+                    board.us(),
+                );
+
+                assert_eq!(
+                    board.gives_check(&move_gen, &info, mv),
+                    expected,
+                    "{fen}: {mv}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn discoverers() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("3k4/8/3N4/8/3R4/8/3P4/B1K2N2 w - - 0 1", &move_gen).unwrap();
+
+        // The knight on d6 blocks the rook, the rook doesn't block anything
+        assert_eq!(
+            board.check_info(&move_gen).unwrap().discoverers,
+            Square::D6.bitboard()
+        );
+    }
+
+    #[test]
+    fn check_info_without_king() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::new();
+        board.set_piece(Piece::King, Color::White, Square::E1);
+        board.set_piece(Piece::Rook, Color::White, Square::A1);
+
+        assert_eq!(board.check_info(&move_gen), None);
+    }
+}
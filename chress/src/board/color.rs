@@ -24,11 +24,24 @@ impl Color {
     pub fn en_passant_rank(&self) -> u8 {
         2 + (*self as u8 * 3)
     }
+
+    /// Parses the active color field of a FEN string, 'w' or 'b'.
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        match c {
+            'w' => Some(Color::White),
+            'b' => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    pub fn to_fen_char(&self) -> char {
+        ['w', 'b'][*self as usize]
+    }
 }
 
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        const STRINGS: [&str; 2] = ["Black", "White"];
+        const STRINGS: [&str; 2] = ["White", "Black"];
         write!(f, "{}", STRINGS[*self as usize])
     }
 }
@@ -43,12 +56,30 @@ mod color_tests {
         assert_eq!(Color::Black.inverse(), Color::White);
     }
 
+    #[test]
+    fn display() {
+        assert_eq!(Color::White.to_string(), "White");
+        assert_eq!(Color::Black.to_string(), "Black");
+    }
+
     #[test]
     fn direction() {
         assert_eq!(Color::White.direction(), 1);
         assert_eq!(Color::Black.direction(), -1);
     }
 
+    #[test]
+    fn fen_chars() {
+        assert_eq!(Color::from_fen_char('w'), Some(Color::White));
+        assert_eq!(Color::from_fen_char('b'), Some(Color::Black));
+        assert_eq!(Color::from_fen_char('W'), None);
+        assert_eq!(Color::from_fen_char('x'), None);
+
+        for color in Color::ALL {
+            assert_eq!(Color::from_fen_char(color.to_fen_char()), Some(color));
+        }
+    }
+
     #[test]
     fn en_passant_rank() {
         assert_eq!(Color::White.en_passant_rank(), 2);
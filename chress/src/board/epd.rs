@@ -0,0 +1,189 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    board::{Board, ParseFenError},
+    move_gen::MoveGen,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseEpdError {
+    WrongSectionCount,
+    /// The position fields, or the `hmvc` and `fmvn` operations, aren't valid FEN.
+    Fen(ParseFenError),
+    /// An operation is missing its opcode or its terminating semicolon.
+    BadOperation,
+}
+
+impl Display for ParseEpdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Error for ParseEpdError {}
+
+/// Splits EPD operations on semicolons outside of quoted strings.
+fn parse_operations(text: &str) -> Result<Vec<(String, String)>, ParseEpdError> {
+    let mut operations = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            ';' if !in_string => {
+                let operation = text[start..i].trim();
+
+                let (opcode, operand) = match operation.split_once(' ') {
+                    Some((opcode, operand)) => (opcode, operand.trim()),
+                    None => (operation, ""),
+                };
+
+                if !opcode.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+                    return Err(ParseEpdError::BadOperation);
+                }
+
+                operations.push((opcode.to_owned(), operand.to_owned()));
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    if !text[start..].trim().is_empty() {
+        return Err(ParseEpdError::BadOperation);
+    }
+
+    Ok(operations)
+}
+
+impl Board {
+    /// Parses an EPD record: the first four fields of a FEN followed by operations such as
+    /// `bm Nf3;`. Returns the position and the operations in order, as opcode and operand.
+    ///
+    /// The halfmove and fullmove clocks are taken from the `hmvc` and `fmvn` operations,
+    /// defaulting to 0 and 1.
+    pub fn from_epd(
+        epd: &str,
+        move_gen: &MoveGen,
+    ) -> Result<(Board, Vec<(String, String)>), ParseEpdError> {
+        let mut fields = epd.trim().splitn(5, ' ');
+
+        let mut position = Vec::new();
+
+        for _ in 0..4 {
+            let Some(field) = fields.next() else {
+                return Err(ParseEpdError::WrongSectionCount);
+            };
+
+            position.push(field);
+        }
+
+        let operations = parse_operations(fields.next().unwrap_or(""))?;
+
+        let clock = |opcode, default| {
+            operations
+                .iter()
+                .find(|(op, _)| op == opcode)
+                .map_or(default, |(_, operand)| operand.as_str())
+        };
+
+        let fen = format!(
+            "{} {} {}",
+            position.join(" "),
+            clock("hmvc", "0"),
+            clock("fmvn", "1")
+        );
+
+        let board = Board::from_fen(&fen, move_gen).map_err(ParseEpdError::Fen)?;
+
+        Ok((board, operations))
+    }
+
+    /// Writes the position as an EPD record, followed by `operations` as opcode and
+    /// operand pairs. Operands are written as given, so strings need their own quotes.
+    ///
+    /// The clocks aren't part of the four EPD position fields, so they are only written if
+    /// `hmvc` and `fmvn` operations are given.
+    pub fn to_epd(&self, operations: &[(String, String)]) -> String {
+        let fen = self.fen();
+        let mut epd = fen.split(' ').take(4).collect::<Vec<&str>>().join(" ");
+
+        for (opcode, operand) in operations {
+            epd.push(' ');
+            epd.push_str(opcode);
+
+            if !operand.is_empty() {
+                epd.push(' ');
+                epd.push_str(operand);
+            }
+
+            epd.push(';');
+        }
+
+        epd
+    }
+}
+
+#[cfg(test)]
+mod epd_tests {
+    use super::*;
+
+    fn operations(operations: &[(&str, &str)]) -> Vec<(String, String)> {
+        operations
+            .iter()
+            .map(|&(opcode, operand)| (opcode.to_owned(), operand.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let move_gen = MoveGen::new();
+
+        let cases = [
+            (
+                "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                operations(&[("bm", "dxe3"), ("id", "\"en passant; test\"")]),
+            ),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 3 12",
+                operations(&[("hmvc", "3"), ("fmvn", "12"), ("noop", "")]),
+            ),
+        ];
+
+        for (fen, operations) in cases {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+            let epd = board.to_epd(&operations);
+
+            assert_eq!(Board::from_epd(&epd, &move_gen), Ok((board, operations)));
+        }
+    }
+
+    #[test]
+    fn to_epd_format() {
+        let board = Board::default();
+
+        assert_eq!(
+            board.to_epd(&operations(&[("bm", "e4"), ("id", "\"start\"")])),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e4; id \"start\";"
+        );
+    }
+
+    #[test]
+    fn bad_epd() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(
+            Board::from_epd("8/8/8/8 w -", &move_gen),
+            Err(ParseEpdError::WrongSectionCount)
+        );
+        assert_eq!(
+            Board::from_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Kd2", &move_gen),
+            Err(ParseEpdError::BadOperation)
+        );
+        assert_eq!(
+            Board::from_epd("4k3/8/8/8/8/8/8/4K3 w - - hmvc x;", &move_gen),
+            Err(ParseEpdError::Fen(ParseFenError::BadHalfmoves))
+        );
+    }
+}
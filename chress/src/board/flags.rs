@@ -7,6 +7,13 @@ use serde::{Deserialize, Serialize};
 
 use super::color::Color;
 
+/// The side of the board a king castles towards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CastleSide {
+    King,
+    Queen,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Flags(pub u8);
 
@@ -57,6 +64,11 @@ impl Flags {
         }
     }
 
+    /// Returns a copy of the flags with en passant marked as valid on the given file.
+    pub fn with_en_passant_file(self, file: u8) -> Self {
+        Self((self.0 & !Self::EP_FILE.0) | Self::EP_IS_VALID.0 | ((file & 0b111) << 4))
+    }
+
     /// Returns the en passant file, regardless of whether en passant is valid or not.
     pub fn en_passant_file_unchecked(&self) -> u8 {
         (self.0 & Self::EP_FILE.0) >> 4
@@ -184,4 +196,22 @@ mod flag_tests {
         assert!(flags.queenside(color));
         assert!(!flags.queenside(color.inverse()));
     }
+
+    #[test]
+    fn en_passant_file_round_trip() {
+        for file in 0..8 {
+            let flags = Flags::UNIVERSE.with_en_passant_file(file);
+
+            assert_eq!(flags.en_passant_file(), Some(file));
+            assert_eq!(flags.en_passant_file_unchecked(), file);
+            // Castling rights are left alone
+            assert_eq!(flags & 0b0000_1111, Flags(0b0000_1111));
+
+            // The file is only reported while en passant is valid
+            let invalid = flags & !Flags::EP_IS_VALID;
+
+            assert_eq!(invalid.en_passant_file(), None);
+            assert_eq!(invalid.en_passant_file_unchecked(), file);
+        }
+    }
 }
@@ -1,12 +1,20 @@
 pub mod bitboard;
+pub mod bound;
+pub mod check;
 pub mod color;
+pub mod epd;
 pub mod flags;
 pub mod r#move;
+pub mod pgn;
 pub mod piece;
+pub mod san;
+pub mod see;
 pub mod sliding_moves;
+pub mod snapshot;
 pub mod square;
+pub mod zobrist;
 
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
@@ -14,12 +22,14 @@ use crate::{
     board::{
         bitboard::Bitboard,
         color::Color,
-        flags::Flags,
+        flags::{CastleSide, Flags},
         piece::Piece,
         r#move::{Move, MoveData},
         square::Square,
+        zobrist::{castling_key, en_passant_key, piece_key, BLACK_TO_MOVE_KEY},
     },
-    move_gen::MoveGen,
+    build::movemasks::PAWN_CAPTURES,
+    move_gen::{king_attacks, knight_attacks, pawn_attacks, MoveGen},
 };
 
 pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
@@ -126,7 +136,7 @@ impl Display for ParseOptionError {
 
 impl Error for ParseOptionError {}
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum ParseFenError {
     BadPosition,
     BadColor,
@@ -146,6 +156,51 @@ impl Display for ParseFenError {
 
 impl Error for ParseFenError {}
 
+/// Decides when a FEN string should include the en passant square.
+///
+/// Parsing accepts both conventions, so this only affects FEN output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnPassantConvention {
+    /// Only write the en passant square if a pawn of the side to move attacks it.
+    Strict,
+    /// Write the en passant square after every double pawn push (X-FEN style).
+    Lazy,
+}
+
+/// How a game ended, as decided by [`Board::game_over`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    /// The given color checkmated its opponent.
+    Checkmate(Color),
+    Stalemate,
+    DrawByFiftyMoves,
+    DrawByInsufficientMaterial,
+    DrawByRepetition,
+}
+
+impl GameResult {
+    /// The result as written at the end of a PGN game: "1-0", "0-1" or "1/2-1/2".
+    pub fn pgn_marker(&self) -> &'static str {
+        match self {
+            GameResult::Checkmate(Color::White) => "1-0",
+            GameResult::Checkmate(Color::Black) => "0-1",
+            _ => "1/2-1/2",
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Checkmate(color) => write!(f, "{color} wins by checkmate"),
+            GameResult::Stalemate => write!(f, "Draw by stalemate"),
+            GameResult::DrawByFiftyMoves => write!(f, "Draw by the fifty move rule"),
+            GameResult::DrawByInsufficientMaterial => write!(f, "Draw by insufficient material"),
+            GameResult::DrawByRepetition => write!(f, "Draw by repetition"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Board {
     pub pieces: [Bitboard; 12],
@@ -155,10 +210,15 @@ pub struct Board {
 
     pub halfmoves: u32,
     pub fullmoves: u32,
+
+    /// Zobrist hash of the position, updated incrementally by `make_move`.
+    pub hash: u64,
 }
 
 impl Board {
-    fn new() -> Self {
+    /// Creates a board with no pieces, White to move and no castling rights, to be filled
+    /// in without going through a FEN string.
+    pub fn new() -> Self {
         Self {
             pieces: [Bitboard::EMPTY; 12],
             active_color: Color::White,
@@ -167,6 +227,8 @@ impl Board {
 
             halfmoves: 0,
             fullmoves: 1,
+
+            hash: 0,
         }
     }
 
@@ -177,6 +239,24 @@ impl Board {
     }
 
     pub fn load_from_fen(&mut self, fen: &str, move_gen: &MoveGen) -> Result<(), ParseFenError> {
+        self.load_fen_structure(fen)?;
+        self.validate(move_gen)
+    }
+
+    /// Checks the parts of a position's validity which need a `MoveGen`, which is
+    /// currently only that the side to move can't capture the enemy king.
+    pub fn validate(&self, move_gen: &MoveGen) -> Result<(), ParseFenError> {
+        // If can capture opponent's king, position is invalid
+        if move_gen.square_attacked_by(self, self.king_square(self.them()), self.us()) {
+            return Err(ParseFenError::InvalidPosition);
+        }
+
+        Ok(())
+    }
+
+    /// Loads a FEN string without checking whether the side to move can capture the
+    /// enemy king. Use [`Board::validate`] to run that check separately.
+    pub fn load_fen_structure(&mut self, fen: &str) -> Result<(), ParseFenError> {
         self.clear_bitboards();
         self.flags.0 = 0;
 
@@ -191,9 +271,13 @@ impl Board {
 
         for char in position.chars() {
             match char {
-                '0'..='8' => {
+                '1'..='8' => {
                     let digit = char.to_digit(9).unwrap() as i8;
                     file += digit;
+
+                    if file > 8 {
+                        return Err(ParseFenError::BadPosition);
+                    }
                 }
                 'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => {
                     let color = if char.is_uppercase() {
@@ -204,12 +288,21 @@ impl Board {
 
                     let piece = Piece::try_from(char).unwrap();
 
+                    if file >= 8 {
+                        return Err(ParseFenError::BadPosition);
+                    }
+
                     let square = Square::try_from(rank as usize * 8 + file as usize).unwrap();
                     self.add_piece(piece, color, square);
 
                     file += 1;
                 }
                 '/' => {
+                    // Every rank must fill exactly 8 files, and there are only 8 ranks
+                    if file != 8 || rank == 0 {
+                        return Err(ParseFenError::BadPosition);
+                    }
+
                     rank -= 1;
                     file = 0;
                 }
@@ -217,6 +310,10 @@ impl Board {
             }
         }
 
+        if rank != 0 || file != 8 {
+            return Err(ParseFenError::BadPosition);
+        }
+
         // Check for kings
         if self.bitboard(Piece::King, Color::White).0.count_ones() != 1 {
             return Err(ParseFenError::InvalidPosition);
@@ -229,23 +326,13 @@ impl Board {
             return Err(ParseFenError::WrongSectionCount);
         };
 
-        self.active_color = match active_color {
-            "w" => Color::White,
-            "b" => Color::Black,
+        let mut chars = active_color.chars();
+
+        self.active_color = match (chars.next(), chars.next()) {
+            (Some(c), None) => Color::from_fen_char(c).ok_or(ParseFenError::BadColor)?,
             _ => return Err(ParseFenError::BadColor),
         };
 
-        let enemy_king_index = self
-            .bitboard(Piece::King, self.active_color.inverse())
-            .0
-            .trailing_zeros();
-        let enemy_king_square = Square::ALL[enemy_king_index as usize];
-
-        // If can capture opponent's king, position is invalid
-        if move_gen.square_attacked_by(self, enemy_king_square, self.active_color) {
-            return Err(ParseFenError::InvalidPosition);
-        }
-
         let Some(castling_rights) = sections.next() else {
             return Err(ParseFenError::WrongSectionCount);
         };
@@ -266,17 +353,33 @@ impl Board {
             return Err(ParseFenError::WrongSectionCount);
         };
 
-        // TODO: Implement checks to prevent invalid en passant squares
         if en_passant != "-" {
-            // Allow en passant
-            self.flags |= Flags::EP_IS_VALID;
-
             let Ok(square) = Square::try_from(en_passant) else {
                 return Err(ParseFenError::BadEnPassant);
             };
 
-            // Set en passant file
-            self.flags.0 |= square.file() << 4;
+            // The opponent must have just pushed a pawn two squares past the en passant
+            // square, so the pawn is in front of it and the squares it crossed are empty
+            let them = self.them();
+
+            if square.rank() != them.en_passant_rank() {
+                return Err(ParseFenError::BadEnPassant);
+            }
+
+            let step = 8 * them.direction() as i32;
+            let pawn_square = Square::ALL[(square as i32 + step) as usize];
+            let start_square = Square::ALL[(square as i32 - step) as usize];
+
+            let pawn_pushed =
+                !(self.bitboard(Piece::Pawn, them) & pawn_square.bitboard()).is_empty();
+            let path_empty =
+                (self.occupied() & (square.bitboard() | start_square.bitboard())).is_empty();
+
+            if !(pawn_pushed && path_empty) {
+                return Err(ParseFenError::BadEnPassant);
+            }
+
+            self.flags = self.flags.with_en_passant_file(square.file());
         }
 
         let Some(halfmoves) = sections.next() else {
@@ -303,10 +406,23 @@ impl Board {
             return Err(ParseFenError::BadFullmoves);
         };
 
+        self.hash = self.zobrist_hash();
+
         Ok(())
     }
 
+    /// Returns the FEN string of the position, writing the en passant square whenever
+    /// it is set on the board.
     pub fn fen(&self) -> String {
+        self.fen_with_en_passant(EnPassantConvention::Lazy)
+    }
+
+    /// Alias of [`Board::fen`].
+    pub fn to_fen(&self) -> String {
+        self.fen()
+    }
+
+    pub fn fen_with_en_passant(&self, convention: EnPassantConvention) -> String {
         let mut fen = String::new();
 
         let mut rank: i8 = 7;
@@ -321,7 +437,7 @@ impl Board {
                     fen.push((tiles_since_last_piece + b'0') as char);
                 }
                 let mut ch = char::from(piece) as u8;
-                if !(self.black_pieces() & square.bitboard()).is_empty() {
+                if !(self.white_pieces() & square.bitboard()).is_empty() {
                     ch -= b'a' - b'A';
                 }
 
@@ -336,24 +452,23 @@ impl Board {
             if file == 8 {
                 file = 0;
                 rank -= 1;
+
+                if tiles_since_last_piece != 0 {
+                    fen.push((tiles_since_last_piece + b'0') as char);
+                }
+                tiles_since_last_piece = 0;
+
                 if rank == -1 {
                     break;
-                } else {
-                    if tiles_since_last_piece != 0 {
-                        fen.push((tiles_since_last_piece + b'0') as char);
-                    }
-                    fen.push('/');
-                    tiles_since_last_piece = 0;
                 }
+
+                fen.push('/');
             }
         }
 
         fen.push(' ');
 
-        fen.push(match self.active_color {
-            Color::White => 'w',
-            Color::Black => 'b',
-        });
+        fen.push(self.active_color.to_fen_char());
 
         fen.push(' ');
 
@@ -380,11 +495,15 @@ impl Board {
 
         fen.push(' ');
 
-        if let Some(file) = self.flags.en_passant_file() {
-            let rank = self.active_color.inverse().en_passant_rank() + 1;
-            let file = (file + b'a') as char;
+        let en_passant_square = match convention {
+            EnPassantConvention::Strict => self
+                .en_passant_square()
+                .filter(|&square| self.en_passant_capturable(square)),
+            EnPassantConvention::Lazy => self.en_passant_square(),
+        };
 
-            fen.push_str(&format!("{file}{rank}",));
+        if let Some(square) = en_passant_square {
+            fen.push_str(&square.to_string());
         } else {
             fen.push('-');
         }
@@ -394,8 +513,181 @@ impl Board {
         fen
     }
 
+    /// Returns the square a pawn could move to by capturing en passant, if en passant
+    /// is set on the board.
+    pub fn en_passant_square(&self) -> Option<Square> {
+        let file = self.flags.en_passant_file()?;
+        let rank = self.them().en_passant_rank();
+
+        Some(Square::ALL[(rank * 8 + file) as usize])
+    }
+
+    /// Checks whether a pawn of the side to move attacks the en passant square.
+    ///
+    /// This does not check whether the capture would leave the king in check.
+    fn en_passant_capturable(&self, square: Square) -> bool {
+        let color = self.active_color;
+        let capturers = PAWN_CAPTURES[color.inverse() as usize][square as usize];
+
+        !(capturers & self.bitboard(Piece::Pawn, color)).is_empty()
+    }
+
+    /// Key of the en passant square in the hash, which is only included when a pawn of
+    /// the side to move can actually capture en passant. FEN strings may give an en passant
+    /// square after every double push, and those positions must still hash the same as
+    /// ones without it.
+    fn en_passant_hash(&self) -> u64 {
+        match self.en_passant_square() {
+            Some(square) if self.en_passant_capturable(square) => en_passant_key(self.flags),
+            _ => 0,
+        }
+    }
+
+    /// The color of the side to move.
+    pub fn us(&self) -> Color {
+        self.active_color
+    }
+
+    /// The color of the side not to move.
+    pub fn them(&self) -> Color {
+        self.active_color.inverse()
+    }
+
+    /// Checks whether `color` still has the right to castle towards `side`. This doesn't
+    /// check whether castling is legal right now, which also needs the squares between the
+    /// king and rook to be empty and the king not to pass through check.
+    pub fn can_castle(&self, color: Color, side: CastleSide) -> bool {
+        match side {
+            CastleSide::King => self.flags.kingside(color),
+            CastleSide::Queen => self.flags.queenside(color),
+        }
+    }
+
+    /// Alias of [`Board::us`].
+    pub fn side_to_move(&self) -> Color {
+        self.us()
+    }
+
+    /// Sets the side to move, keeping the hash up to date.
+    ///
+    /// Changing the side to move also clears the en passant square, as it can only be
+    /// valid directly after the other side's double pawn push.
+    pub fn set_active_color(&mut self, color: Color) {
+        if color == self.active_color {
+            return;
+        }
+
+        self.hash ^= self.en_passant_hash();
+        self.flags &= !Flags::EP_IS_VALID;
+
+        self.flip_color();
+    }
+
+    /// Number of plies since the last capture or pawn move.
+    ///
+    /// Neither of those can be undone, so no position before them can occur again. Only
+    /// this many previous positions need to be checked when looking for repetitions.
+    pub fn reversible_plies(&self) -> u32 {
+        self.halfmoves
+    }
+
+    /// Counts how many times the current position occurred before, given the hashes of
+    /// the previous positions in the game, oldest first.
+    ///
+    /// Only the last [`Board::reversible_plies`] positions are scanned.
+    pub fn repetitions(&self, history: &[u64]) -> usize {
+        history
+            .iter()
+            .rev()
+            .take(self.reversible_plies() as usize)
+            .filter(|&&hash| hash == self.hash)
+            .count()
+    }
+
+    /// Checks whether the side to move is in check.
+    pub fn is_in_check(&self, move_gen: &MoveGen) -> bool {
+        self.is_color_in_check(self.active_color, move_gen)
+    }
+
+    /// Checks whether the king of `color` is attacked, whichever side is to move.
+    pub fn is_color_in_check(&self, color: Color, move_gen: &MoveGen) -> bool {
+        move_gen.square_attacked_by(self, self.king_square(color), color.inverse())
+    }
+
+    /// Checks whether the side to move has exactly one legal move.
+    pub fn is_forced(&self, move_gen: &MoveGen) -> bool {
+        let mut moves = Vec::new();
+        move_gen.legal_moves(self, &mut moves) == 1
+    }
+
+    /// Checks whether the game is over by checkmate, stalemate, insufficient material or
+    /// the fifty move rule.
+    ///
+    /// Checkmate takes precedence over the fifty move rule. Repetitions aren't detected,
+    /// since they depend on the game's history; see [`bound::BoundBoard::game_over`].
+    pub fn game_over(&self, move_gen: &MoveGen) -> Option<GameResult> {
+        let mut moves = Vec::new();
+
+        if move_gen.legal_moves(self, &mut moves) == 0 {
+            return Some(if self.is_in_check(move_gen) {
+                GameResult::Checkmate(self.them())
+            } else {
+                GameResult::Stalemate
+            });
+        }
+
+        if self.has_insufficient_material() {
+            return Some(GameResult::DrawByInsufficientMaterial);
+        }
+
+        (self.halfmoves >= 100).then_some(GameResult::DrawByFiftyMoves)
+    }
+
     pub fn flip_color(&mut self) {
         self.active_color = self.active_color.inverse();
+        self.hash ^= BLACK_TO_MOVE_KEY;
+    }
+
+    /// Resets the board to an empty position in place, with White to move, no castling
+    /// rights or en passant square and the move counters reset.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Places a piece on `square`, replacing whatever was there, and updates the hash.
+    pub fn set_piece(&mut self, piece: Piece, color: Color, square: Square) {
+        // Whether en passant is hashed depends on the pawns next to the en passant square
+        self.hash ^= self.en_passant_hash();
+
+        if let Some(existing) = self.piece_at(square) {
+            let existing_color = if (self.white_pieces() & square.bitboard()).is_empty() {
+                Color::Black
+            } else {
+                Color::White
+            };
+
+            self.remove_piece(existing, existing_color, square);
+            self.hash ^= piece_key(existing, existing_color, square);
+        }
+
+        self.add_piece(piece, color, square);
+        self.hash ^= piece_key(piece, color, square);
+
+        self.hash ^= self.en_passant_hash();
+    }
+
+    /// Sets the castling rights, keeping the hash up to date. Only the castling bits of
+    /// `rights` are used, so the en passant square is left as it is.
+    pub fn set_castling_rights(&mut self, rights: Flags) {
+        let castling = Flags::WHITE_KINGSIDE
+            | Flags::WHITE_QUEENSIDE
+            | Flags::BLACK_KINGSIDE
+            | Flags::BLACK_QUEENSIDE;
+
+        let flags = (self.flags & !castling) | (rights & castling);
+
+        self.hash ^= castling_key(self.flags ^ flags);
+        self.flags = flags;
     }
 
     fn clear_bitboards(&mut self) {
@@ -404,15 +696,23 @@ impl Board {
         }
     }
 
+    #[inline]
     pub fn bitboard(&self, piece: Piece, color: Color) -> Bitboard {
         self.pieces[Self::bitboard_index(piece, color)]
     }
 
+    /// Returns the square of the king of the given color.
+    pub fn king_square(&self, color: Color) -> Square {
+        let king_index = self.bitboard(Piece::King, color).0.trailing_zeros();
+        Square::ALL[king_index as usize]
+    }
+
     fn bitboard_mut(&mut self, piece: Piece, color: Color) -> &mut Bitboard {
         &mut self.pieces[Self::bitboard_index(piece, color)]
     }
 
-    fn bitboard_index(piece: Piece, color: Color) -> usize {
+    #[inline]
+    const fn bitboard_index(piece: Piece, color: Color) -> usize {
         piece as usize + (color as usize * 6)
     }
 
@@ -424,11 +724,21 @@ impl Board {
         *self.bitboard_mut(piece, color) &= !square.bitboard();
     }
 
+    #[inline]
     pub fn occupied(&self) -> Bitboard {
         self.white_pieces() | self.black_pieces()
     }
 
     pub fn white_pieces(&self) -> Bitboard {
+        self.pieces[0]
+            | self.pieces[1]
+            | self.pieces[2]
+            | self.pieces[3]
+            | self.pieces[4]
+            | self.pieces[5]
+    }
+
+    pub fn black_pieces(&self) -> Bitboard {
         self.pieces[6]
             | self.pieces[7]
             | self.pieces[8]
@@ -437,21 +747,20 @@ impl Board {
             | self.pieces[11]
     }
 
-    pub fn black_pieces(&self) -> Bitboard {
-        self.pieces[0]
-            | self.pieces[1]
-            | self.pieces[2]
-            | self.pieces[3]
-            | self.pieces[4]
-            | self.pieces[5]
+    /// Returns all pieces of the given color.
+    pub fn color_pieces(&self, color: Color) -> Bitboard {
+        match color {
+            Color::White => self.white_pieces(),
+            Color::Black => self.black_pieces(),
+        }
     }
 
     pub fn white_bitboards(&self) -> &[Bitboard] {
-        &self.pieces[6..12]
+        &self.pieces[0..6]
     }
 
     pub fn black_bitboards(&self) -> &[Bitboard] {
-        &self.pieces[0..6]
+        &self.pieces[6..12]
     }
 
     pub fn piece_at(&self, square: Square) -> Option<Piece> {
@@ -479,6 +788,130 @@ impl Board {
         PIECES[piece_at_square_index]
     }
 
+    /// Returns the squares attacked by the piece on `square`, or an empty bitboard if the
+    /// square is empty.
+    pub fn attacks_from(&self, move_gen: &MoveGen, square: Square) -> Bitboard {
+        let Some(piece) = self.piece_at(square) else {
+            return Bitboard::EMPTY;
+        };
+
+        let color = if (self.white_pieces() & square.bitboard()).is_empty() {
+            Color::Black
+        } else {
+            Color::White
+        };
+
+        let occupied = self.occupied();
+
+        match piece {
+            Piece::Knight => knight_attacks(square),
+            Piece::Bishop => move_gen.bishop_attacks(square, occupied),
+            Piece::Rook => move_gen.rook_attacks(square, occupied),
+            Piece::Queen => move_gen.queen_attacks(square, occupied),
+            Piece::King => king_attacks(square),
+            Piece::Pawn => pawn_attacks(square, color),
+        }
+    }
+
+    /// Returns how many pieces of `color` attack `square`.
+    pub fn count_attackers(&self, move_gen: &MoveGen, square: Square, color: Color) -> u32 {
+        let attackers = move_gen.attackers_to(self, square, self.occupied());
+
+        (attackers & self.color_pieces(color)).0.count_ones()
+    }
+
+    /// Checks whether `color` has enough material to force checkmate against a lone king:
+    /// any pawn, rook or queen, a bishop and a knight, or bishops on both square colors.
+    ///
+    /// Two knights (or only same-colored bishops) count as not having mating material.
+    /// Mate is possible with them, but it can't be forced.
+    pub fn has_mating_material(&self, color: Color) -> bool {
+        let heavy = self.bitboard(Piece::Pawn, color)
+            | self.bitboard(Piece::Rook, color)
+            | self.bitboard(Piece::Queen, color);
+
+        if !heavy.is_empty() {
+            return true;
+        }
+
+        let knights = self.bitboard(Piece::Knight, color);
+        let bishops = self.bitboard(Piece::Bishop, color);
+
+        let both_bishop_colors = !(bishops & Bitboard::LIGHT_SQUARES).is_empty()
+            && !(bishops & Bitboard::DARK_SQUARES).is_empty();
+
+        both_bishop_colors || (!knights.is_empty() && !bishops.is_empty())
+    }
+
+    /// Checks whether neither side can possibly checkmate: king against king, king and a
+    /// single minor piece against king, or kings and bishops which are all on squares of
+    /// the same color.
+    ///
+    /// Positions where mate is possible but can't be forced, like two knights against a
+    /// lone king, aren't counted.
+    pub fn has_insufficient_material(&self) -> bool {
+        let mut heavy = Bitboard::EMPTY;
+        let mut knights = Bitboard::EMPTY;
+        let mut bishops = Bitboard::EMPTY;
+
+        for color in Color::ALL {
+            heavy |= self.bitboard(Piece::Pawn, color)
+                | self.bitboard(Piece::Rook, color)
+                | self.bitboard(Piece::Queen, color);
+            knights |= self.bitboard(Piece::Knight, color);
+            bishops |= self.bitboard(Piece::Bishop, color);
+        }
+
+        if !heavy.is_empty() {
+            return false;
+        }
+
+        match (knights.0.count_ones(), bishops.0.count_ones()) {
+            (0, count) => {
+                let mut light = 0;
+
+                for _ in 0..count {
+                    if Square::ALL[bishops.pop_lsb() as usize].is_light() {
+                        light += 1;
+                    }
+                }
+
+                light == 0 || light == count
+            }
+            (1, 0) => true,
+            _ => false,
+        }
+    }
+
+    /// Looks up the pieces on two squares at once, sharing the work of combining the
+    /// bitboards of both colors. Equivalent to `(self.piece_at(a), self.piece_at(b))`.
+    pub fn pieces_on(&self, a: Square, b: Square) -> (Option<Piece>, Option<Piece>) {
+        const PIECES: [Option<Piece>; 7] = [
+            None,
+            Some(Piece::Knight),
+            Some(Piece::Bishop),
+            Some(Piece::Rook),
+            Some(Piece::Queen),
+            Some(Piece::King),
+            Some(Piece::Pawn),
+        ];
+
+        let a = a as u32;
+        let b = b as u32;
+
+        let mut index_a = 0;
+        let mut index_b = 0;
+
+        for i in 0..6 {
+            let pieces = (self.pieces[i] | self.pieces[i + 6]).0;
+
+            index_a |= ((pieces >> a) & 1) as usize * (i + 1);
+            index_b |= ((pieces >> b) & 1) as usize * (i + 1);
+        }
+
+        (PIECES[index_a], PIECES[index_b])
+    }
+
     pub fn friendly_pieces(&self) -> Bitboard {
         let off = self.active_color as usize * 6;
 
@@ -512,20 +945,26 @@ impl Board {
         let color = self.active_color;
         let from = r#move.from();
         let to = r#move.to();
-        let promotion = r#move.promotion();
+        let promotion = r#move.promotion().map(Piece::from);
+
+        let (moved_piece, captured_piece) = self.pieces_on(from, to);
 
-        let Some(moved_piece) = self.piece_at(from) else {
+        let Some(moved_piece) = moved_piece else {
             return Err(MakeMoveError);
         };
 
         // Create new move_data struct
         let mut move_data = MoveData {
             r#move,
-            captured_piece: self.piece_at(to),
+            captured_piece,
             halfmoves: self.halfmoves,
             flags: self.flags,
+            hash: self.hash,
         };
 
+        let captured_on_to = move_data.captured_piece;
+        let mut hash = self.hash ^ BLACK_TO_MOVE_KEY ^ self.en_passant_hash();
+
         // Increment halfmoves
         // Will be overwritten if necessary
         self.halfmoves += 1;
@@ -537,10 +976,18 @@ impl Board {
 
             let is_double_move = from.rank().abs_diff(to.rank()) == 2;
 
-            // Unset en passant file bits if necessary
-            self.flags &= !(Flags::EP_FILE * is_double_move);
+            // Only mark en passant as valid if an enemy pawn attacks the skipped square,
+            // so that positions which only differ by an unusable en passant square compare equal
+            let skipped_square = Square::ALL[(from as usize + to as usize) / 2];
+            let enemy_pawns = self.bitboard(Piece::Pawn, color.inverse());
+            let capturable =
+                !(PAWN_CAPTURES[color as usize][skipped_square as usize] & enemy_pawns).is_empty();
+
+            // Unset en passant bits if necessary
+            self.flags &= !((Flags::EP_FILE | Flags::EP_IS_VALID) * is_double_move);
             // Set ep flag and ep file data correctly
-            self.flags |= (Flags::EP_IS_VALID | Flags(from.file() << 4)) * is_double_move;
+            self.flags |=
+                Flags::EMPTY.with_en_passant_file(from.file()) * (is_double_move && capturable);
 
             // En passant
             if !is_double_move {
@@ -565,6 +1012,7 @@ impl Board {
                     move_data.captured_piece = Some(Piece::Pawn);
 
                     self.remove_piece(Piece::Pawn, color.inverse(), capture_square);
+                    hash ^= piece_key(Piece::Pawn, color.inverse(), capture_square);
                 }
             }
         } else {
@@ -583,6 +1031,15 @@ impl Board {
         let rook_move_mask = ROOK_CASTLING_MOVEMASKS[to as usize];
         *self.bitboard_mut(Piece::Rook, color) ^= rook_move_mask * is_castling;
 
+        if is_castling {
+            let mut rook_squares = rook_move_mask;
+
+            while !rook_squares.is_empty() {
+                let square = Square::ALL[rook_squares.pop_lsb() as usize];
+                hash ^= piece_key(Piece::Rook, color, square);
+            }
+        }
+
         // Castling rights
         let is_rook = moved_piece == Piece::Rook;
         let reset_mask = Flags::UNIVERSE * !is_rook;
@@ -591,30 +1048,91 @@ impl Board {
         self.flags &= CASTLING_RIGHTS_FLAGS[to as usize];
 
         self.remove_piece(moved_piece, color, from);
+        hash ^= piece_key(moved_piece, color, from);
 
         // If promotion, create a new piece of the correct type
         if let Some(promoted_piece) = promotion {
             self.add_piece(promoted_piece, color, to);
+            hash ^= piece_key(promoted_piece, color, to);
         }
         // Otherwise, just move the piece as normal
         else {
             self.add_piece(moved_piece, color, to);
+            hash ^= piece_key(moved_piece, color, to);
         }
 
         // Remove any captured pieces
         if let Some(captured_piece) = move_data.captured_piece {
             self.remove_piece(captured_piece, color.inverse(), to);
+            self.halfmoves = 0;
+        }
+
+        // En passant captures were already hashed on the captured pawn's square
+        if let Some(captured_piece) = captured_on_to {
+            hash ^= piece_key(captured_piece, color.inverse(), to);
         }
 
+        // Castling rights may be lost in bulk, so toggle the key of every right that changed
+        hash ^= castling_key(Flags(move_data.flags.0 ^ self.flags.0));
+        // Double pushes only set en passant when it can be captured
+        hash ^= en_passant_key(self.flags);
+
+        self.hash = hash;
+
         // Swap colors
         self.active_color = self.active_color.inverse();
 
-        // Update fullmove count
-        self.fullmoves += color.inverse() as u32;
+        // Update fullmove count, which increments after Black's move
+        self.fullmoves += color as u32;
+
+        Ok(move_data)
+    }
+
+    /// Plays a move on the board, failing without changing the board if the move isn't
+    /// legal in the current position.
+    pub fn make_move_checked(
+        &mut self,
+        r#move: Move,
+        move_gen: &MoveGen,
+    ) -> Result<MoveData, MakeMoveError> {
+        let mut moves = Vec::new();
+        move_gen.legal_moves(self, &mut moves);
+
+        if !moves.contains(&r#move) {
+            return Err(MakeMoveError);
+        }
+
+        self.make_move(r#move)
+    }
+
+    /// Plays several moves in order, returning the data to unmake them with
+    /// [`Board::unmake_moves`]. If any move fails, the board is left unchanged.
+    pub fn make_moves(&mut self, moves: &[Move]) -> Result<Vec<MoveData>, MakeMoveError> {
+        let original = *self;
+        let mut move_data = Vec::with_capacity(moves.len());
+
+        for &mv in moves {
+            match self.make_move(mv) {
+                Ok(data) => move_data.push(data),
+                Err(err) => {
+                    *self = original;
+                    return Err(err);
+                }
+            }
+        }
 
         Ok(move_data)
     }
 
+    /// Unmakes moves made by [`Board::make_moves`], latest first.
+    pub fn unmake_moves(&mut self, move_data: &[MoveData]) -> Result<(), UnmakeMoveError> {
+        for &data in move_data.iter().rev() {
+            self.unmake_move(data)?;
+        }
+
+        Ok(())
+    }
+
     // ! 4 branches, but they may be irreplaceable / too expensive to remove
     /// Unmakes a move on the board by popping the most recent move data off the stack.
     ///
@@ -623,7 +1141,7 @@ impl Board {
     pub fn unmake_move(&mut self, move_data: MoveData) -> Result<(), UnmakeMoveError> {
         let from = move_data.r#move.from();
         let to = move_data.r#move.to();
-        let promotion = move_data.r#move.promotion();
+        let promotion = move_data.r#move.promotion().map(Piece::from);
         let color = self.active_color.inverse();
 
         let piece_at_to: Piece;
@@ -683,20 +1201,70 @@ impl Board {
 
         // Set move data
         self.halfmoves = move_data.halfmoves;
-        self.fullmoves -= self.active_color as u32;
+        self.fullmoves -= color as u32;
 
         self.flags = move_data.flags;
+        self.hash = move_data.hash;
 
         self.active_color = color;
 
         Ok(())
     }
+
+    /// Passes the turn to the other side without moving a piece, clearing any en passant
+    /// square. Used by null-move pruning in search.
+    ///
+    /// The halfmove clock is reset, so that [`Board::repetitions`] doesn't count positions
+    /// from before the null move: a line containing a null move isn't a real repetition.
+    pub fn make_null_move(&mut self) -> MoveData {
+        let move_data = MoveData {
+            r#move: Move::NULLMOVE,
+            captured_piece: None,
+            halfmoves: self.halfmoves,
+            flags: self.flags,
+            hash: self.hash,
+        };
+
+        self.halfmoves = 0;
+        self.set_active_color(self.active_color.inverse());
+
+        move_data
+    }
+
+    /// Undoes [`Board::make_null_move`].
+    pub fn unmake_null_move(&mut self, move_data: MoveData) {
+        self.active_color = self.active_color.inverse();
+        self.halfmoves = move_data.halfmoves;
+        self.flags = move_data.flags;
+        self.hash = move_data.hash;
+    }
+}
+
+/// Leniently parses a FEN string. The structure is fully validated, but unlike
+/// [`Board::from_fen`] positions where the side to move can capture the enemy king are
+/// accepted.
+impl TryFrom<&str> for Board {
+    type Error = ParseFenError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut board = Board::new();
+        board.load_fen_structure(value)?;
+        Ok(board)
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseFenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::try_from(s)
+    }
 }
 
 impl Default for Board {
     /// Creates a new instance of Board with the starting position loaded.
     fn default() -> Self {
-        Board {
+        let mut board = Board {
             pieces: [
                 // White knights
                 Bitboard(66),
@@ -728,7 +1296,13 @@ impl Default for Board {
             flags: Flags(0b0000_1111),
             halfmoves: 0,
             fullmoves: 1,
-        }
+
+            hash: 0,
+        };
+
+        board.hash = board.zobrist_hash();
+
+        board
     }
 }
 
@@ -790,13 +1364,773 @@ mod board_tests {
     pub const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
 
     #[test]
-    fn board_default() {
-        assert_eq!(
+    fn null_move() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+            &move_gen,
+        )
+        .unwrap();
+        let original = board;
+
+        let move_data = board.make_null_move();
+
+        assert_eq!(board.active_color, Color::White);
+        assert_eq!(board.en_passant_square(), None);
+        assert_eq!(board.hash, board.zobrist_hash());
+
+        board.unmake_null_move(move_data);
+
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn null_moves_are_not_repetitions() {
+        let mut board = Board::default();
+        let mut history = Vec::new();
+
+        // Nf3 Nf6, then two null moves and Ng1 Ng8 return to the starting position
+        for mv in ["g1f3", "g8f6", "", "", "f3g1", "f6g8"] {
+            history.push(board.hash);
+
+            if mv.is_empty() {
+                board.make_null_move();
+            } else {
+                board.make_move(Move::try_from(mv).unwrap()).unwrap();
+            }
+        }
+
+        assert_eq!(board.hash, Board::default().hash);
+        assert_eq!(history[0], board.hash);
+        assert_eq!(board.repetitions(&history), 0);
+
+        // The same line without the null moves is a repetition
+        let mut board = Board::default();
+        let mut history = Vec::new();
+
+        for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            history.push(board.hash);
+            board.make_move(Move::try_from(mv).unwrap()).unwrap();
+        }
+
+        assert_eq!(board.repetitions(&history), 1);
+    }
+
+    #[test]
+    fn make_unmake_restores_board() {
+        let move_gen = MoveGen::new();
+
+        let fens = [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            POSITION_5,
+        ];
+
+        for fen in fens {
+            let mut board = Board::from_fen(fen, &move_gen).unwrap();
+            let original = board;
+
+            let mut moves = Vec::new();
+            move_gen.legal_moves(&board, &mut moves);
+
+            for mv in moves {
+                let move_data = board.make_move(mv).unwrap();
+                board.unmake_move(move_data).unwrap();
+
+                assert_eq!(board, original, "{fen}: {mv}");
+            }
+        }
+    }
+
+    #[test]
+    fn attacks_from() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(POSITION_5, &move_gen).unwrap();
+
+        let attacks = |square| {
+            let mut bitboard = board.attacks_from(&move_gen, square);
+            let mut squares = Vec::new();
+
+            while !bitboard.is_empty() {
+                squares.push(Square::ALL[bitboard.pop_lsb() as usize].to_string());
+            }
+
+            squares.sort();
+            squares
+        };
+
+        let cases: [(Square, &[&str]); 9] = [
+            // Knight, pawns of both colors and an empty square
+            (Square::F2, &["d1", "d3", "e4", "g4", "h1", "h3"]),
+            (Square::D7, &["c8", "e8"]),
+            (Square::C6, &["b5", "d5"]),
+            (Square::E4, &[]),
+            // Bishops, king, rook and queen, which attack pieces of either color
+            (
+                Square::C4,
+                &["a2", "a6", "b3", "b5", "d3", "d5", "e2", "e6", "f7"],
+            ),
+            (Square::E1, &["d1", "d2", "e2", "f1", "f2"]),
+            (Square::H1, &["e1", "f1", "g1", "h2"]),
+            (
+                Square::D1,
+                &["c1", "c2", "d2", "d3", "d4", "d5", "d6", "d7", "e1", "e2"],
+            ),
+            (
+                Square::E7,
+                &["a3", "b4", "c5", "d6", "d8", "f6", "f8", "g5", "h4"],
+            ),
+        ];
+
+        for (square, expected) in cases {
+            assert_eq!(attacks(square), expected, "{square}");
+        }
+    }
+
+    #[test]
+    fn new_board_is_empty() {
+        let board = Board::new();
+
+        assert!(board.occupied().is_empty());
+        assert!(Square::ALL
+            .iter()
+            .all(|&square| board.piece_at(square).is_none()));
+        assert_eq!(board.active_color, Color::White);
+        assert_eq!(board.flags, Flags(0));
+    }
+
+    #[test]
+    fn clear_then_set_up_start_position() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(POSITION_5, &move_gen).unwrap();
+
+        board.clear();
+        assert_eq!(board, Board::new());
+
+        let back_rank = [
+            Piece::Rook,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Queen,
+            Piece::King,
+            Piece::Bishop,
+            Piece::Knight,
+            Piece::Rook,
+        ];
+
+        for (file, piece) in back_rank.into_iter().enumerate() {
+            board.set_piece(piece, Color::White, Square::ALL[file]);
+            board.set_piece(Piece::Pawn, Color::White, Square::ALL[8 + file]);
+            board.set_piece(Piece::Pawn, Color::Black, Square::ALL[48 + file]);
+            board.set_piece(piece, Color::Black, Square::ALL[56 + file]);
+        }
+
+        board.set_castling_rights(
+            Flags::WHITE_KINGSIDE
+                | Flags::WHITE_QUEENSIDE
+                | Flags::BLACK_KINGSIDE
+                | Flags::BLACK_QUEENSIDE,
+        );
+
+        assert_eq!(board, Board::default());
+    }
+
+    #[test]
+    fn set_castling_rights() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            &move_gen,
+        )
+        .unwrap();
+
+        board.set_castling_rights(Flags::WHITE_KINGSIDE | Flags::BLACK_QUEENSIDE | Flags::EP_FILE);
+
+        assert_eq!(board.hash, board.zobrist_hash());
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3"
+        );
+
+        board.set_castling_rights(Flags::EMPTY);
+
+        assert_eq!(board.hash, board.zobrist_hash());
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w - f6 0 3"
+        );
+    }
+
+    #[test]
+    fn set_piece_next_to_en_passant_square() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            &move_gen,
+        )
+        .unwrap();
+
+        // Replacing the only pawn which can capture en passant
+        board.set_piece(Piece::Knight, Color::White, Square::E5);
+        assert_eq!(board.hash, board.zobrist_hash());
+
+        // Putting it back
+        board.set_piece(Piece::Pawn, Color::White, Square::E5);
+        assert_eq!(board.hash, board.zobrist_hash());
+
+        // Adding a second capturing pawn
+        board.set_piece(Piece::Pawn, Color::White, Square::G5);
+        assert_eq!(board.hash, board.zobrist_hash());
+    }
+
+    #[test]
+    fn set_piece_replaces_existing_piece() {
+        let mut board = Board::default();
+
+        board.set_piece(Piece::Queen, Color::Black, Square::E2);
+
+        assert_eq!(board.piece_at(Square::E2), Some(Piece::Queen));
+        assert!((board.white_pieces() & Square::E2.bitboard()).is_empty());
+        assert_eq!(board.bitboard(Piece::Pawn, Color::White).0.count_ones(), 7);
+        assert_eq!(board.hash, board.zobrist_hash());
+    }
+
+    #[test]
+    fn can_castle() {
+        let mut board = Board::default();
+
+        for color in Color::ALL {
+            assert!(board.can_castle(color, CastleSide::King));
+            assert!(board.can_castle(color, CastleSide::Queen));
+        }
+
+        for mv in ["e2e4", "e7e5", "e1e2"] {
+            board.make_move(Move::try_from(mv).unwrap()).unwrap();
+        }
+
+        assert!(!board.can_castle(Color::White, CastleSide::King));
+        assert!(!board.can_castle(Color::White, CastleSide::Queen));
+        assert!(board.can_castle(Color::Black, CastleSide::King));
+        assert!(board.can_castle(Color::Black, CastleSide::Queen));
+    }
+
+    #[test]
+    fn capturing_rook_removes_castling_rights() {
+        let move_gen = MoveGen::new();
+
+        // A bishop takes the h8 rook and a knight the a8 rook, neither being rooks
+        // themselves
+        let cases = [
+            (
+                "r3k2r/8/8/8/3B4/8/8/4K3 w kq - 0 1",
+                "d4h8",
+                CastleSide::King,
+                "e8c8",
+            ),
+            (
+                "r3k2r/8/1N6/8/8/8/8/4K3 w kq - 0 1",
+                "b6a8",
+                CastleSide::Queen,
+                "e8g8",
+            ),
+        ];
+
+        for (fen, capture, lost, remaining) in cases {
+            let mut board = Board::from_fen(fen, &move_gen).unwrap();
+            let original = board;
+
+            let move_data = board.make_move(Move::try_from(capture).unwrap()).unwrap();
+
+            assert!(!board.can_castle(Color::Black, lost), "{fen}");
+            assert_eq!(board.hash, board.zobrist_hash(), "{fen}");
+
+            let mut moves = Vec::new();
+            move_gen.legal_moves(&board, &mut moves);
+
+            let castling = moves
+                .iter()
+                .filter(|mv| {
+                    mv.from() == Square::E8 && mv.from().file().abs_diff(mv.to().file()) == 2
+                })
+                .map(Move::to_string)
+                .collect::<Vec<String>>();
+
+            assert_eq!(castling, [remaining], "{fen}");
+
+            board.unmake_move(move_data).unwrap();
+            assert_eq!(board, original, "{fen}");
+        }
+    }
+
+    #[test]
+    fn is_in_check() {
+        let move_gen = MoveGen::new();
+
+        assert!(!Board::default().is_in_check(&move_gen));
+
+        // The black king on h5 is checked by the bishop on e2, the white king on a1 isn't
+        let board = Board::from_fen("8/8/8/7k/8/8/4B3/K7 b - - 0 1", &move_gen).unwrap();
+
+        assert!(board.is_in_check(&move_gen));
+        assert!(board.is_color_in_check(Color::Black, &move_gen));
+        assert!(!board.is_color_in_check(Color::White, &move_gen));
+
+        // The white king in the middle of the board is checked by a knight
+        let board = Board::from_fen("k7/8/8/2n5/4K3/8/8/8 w - - 0 1", &move_gen).unwrap();
+
+        assert!(board.is_in_check(&move_gen));
+        assert!(!board.is_color_in_check(Color::Black, &move_gen));
+
+        // Blocked checks don't count
+        let board = Board::from_fen("k7/8/8/8/4K2r/8/8/8 w - - 0 1", &move_gen).unwrap();
+        assert!(board.is_in_check(&move_gen));
+
+        let board = Board::from_fen("k7/8/8/8/4K1Pr/8/8/8 w - - 0 1", &move_gen).unwrap();
+        assert!(!board.is_in_check(&move_gen));
+    }
+
+    #[test]
+    fn rejects_malformed_piece_placement() {
+        let move_gen = MoveGen::new();
+
+        for position in [
+            // Zero empty squares
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/0RNBQKBNR",
+            // Too few and too many files in a rank
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPP/RNBQKBNR",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPPP/RNBQKBNR",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR",
+            // Too few and too many ranks
+            "rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR",
+            "rnbqkbnr/pppppppp/8/8/8/8/8/PPPPPPPP/RNBQKBNR",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/",
+        ] {
+            let fen = format!("{position} w KQkq - 0 1");
+
+            assert_eq!(
+                Board::from_fen(&fen, &move_gen),
+                Err(ParseFenError::BadPosition),
+                "{fen}"
+            );
+        }
+
+        assert!(Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/35/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &move_gen
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_en_passant_squares() {
+        let move_gen = MoveGen::new();
+
+        // After 1. e4 and 1. e4 d5 2. e5 f5
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            assert!(Board::from_fen(fen, &move_gen).is_ok(), "{fen}");
+        }
+
+        for fen in [
+            // Wrong rank for the side to move
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e6 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e1 0 1",
+            // No pawn in front of the square
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq d3 0 1",
+            // The pawn can't have come from e2
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPPNPPP/RNBQKB1R b KQkq e3 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/4N3/PPPP1PPP/RNBQKB1R b KQkq e3 0 1",
+        ] {
+            assert_eq!(
+                Board::from_fen(fen, &move_gen),
+                Err(ParseFenError::BadEnPassant),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn has_insufficient_material() {
+        let move_gen = MoveGen::new();
+
+        let cases = [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4K1n1 w - - 0 1", true),
+            // Bishops on f1 and c8 are both on light squares
+            ("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            // Bishops on f1 and f8 aren't
+            ("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KBN1 w - - 0 1", false),
+            ("4k1n1/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4K2R w - - 0 1", false),
+            (START_FEN, false),
+        ];
+
+        for (fen, expected) in cases {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            assert_eq!(board.has_insufficient_material(), expected, "{fen}");
+        }
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K1n1 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.game_over(&move_gen),
+            Some(GameResult::DrawByInsufficientMaterial)
+        );
+    }
+
+    #[test]
+    fn make_and_unmake_moves() {
+        let moves = [
+            "e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "d5c6", "d8d2", "e1d2",
+        ]
+        .map(|mv| Move::try_from(mv).unwrap());
+
+        let mut board = Board::default();
+        let move_data = board.make_moves(&moves).unwrap();
+
+        assert_eq!(move_data.len(), moves.len());
+        assert_eq!(
+            board.fen(),
+            "rnb1kb1r/pp2pppp/2P2n2/1B6/8/8/PPPK1PPP/RNBQ2NR b kq - 0 5"
+        );
+
+        board.unmake_moves(&move_data).unwrap();
+        assert_eq!(board, Board::default());
+
+        // Nothing is played if any move fails, here because e2 is empty after e4
+        let bad = [moves[0], Move::try_from("e2e3").unwrap()];
+        assert!(board.make_moves(&bad).is_err());
+        assert_eq!(board, Board::default());
+    }
+
+    #[test]
+    fn game_result_strings() {
+        let cases = [
+            (
+                GameResult::Checkmate(Color::White),
+                "White wins by checkmate",
+                "1-0",
+            ),
+            (
+                GameResult::Checkmate(Color::Black),
+                "Black wins by checkmate",
+                "0-1",
+            ),
+            (GameResult::Stalemate, "Draw by stalemate", "1/2-1/2"),
+            (
+                GameResult::DrawByFiftyMoves,
+                "Draw by the fifty move rule",
+                "1/2-1/2",
+            ),
+            (
+                GameResult::DrawByInsufficientMaterial,
+                "Draw by insufficient material",
+                "1/2-1/2",
+            ),
+            (
+                GameResult::DrawByRepetition,
+                "Draw by repetition",
+                "1/2-1/2",
+            ),
+        ];
+
+        for (result, text, marker) in cases {
+            assert_eq!(result.to_string(), text);
+            assert_eq!(result.pgn_marker(), marker);
+        }
+    }
+
+    #[test]
+    fn game_over() {
+        let move_gen = MoveGen::new();
+
+        let cases = [
+            (START_FEN, None),
+            // Back rank mate
+            (
+                "R5k1/5ppp/8/8/8/8/8/4K3 b - - 0 1",
+                Some(GameResult::Checkmate(Color::White)),
+            ),
+            // Fool's mate
+            (
+                "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+                Some(GameResult::Checkmate(Color::Black)),
+            ),
+            (
+                "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+                Some(GameResult::Stalemate),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2R w - - 100 80",
+                Some(GameResult::DrawByFiftyMoves),
+            ),
+            ("4k3/8/8/8/8/8/8/4K2R w - - 99 80", None),
+            // Mating on the hundredth halfmove still wins
+            (
+                "R5k1/5ppp/8/8/8/8/8/4K3 b - - 100 80",
+                Some(GameResult::Checkmate(Color::White)),
+            ),
+        ];
+
+        for (fen, expected) in cases {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            assert_eq!(board.game_over(&move_gen), expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn has_mating_material() {
+        let move_gen = MoveGen::new();
+
+        let cases = [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/4B3/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KBN1 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4K2R w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", true),
+        ];
+
+        for (fen, expected) in cases {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            assert_eq!(board.has_mating_material(Color::White), expected, "{fen}");
+            assert!(!board.has_mating_material(Color::Black), "{fen}");
+        }
+    }
+
+    #[test]
+    fn count_attackers() {
+        let move_gen = MoveGen::new();
+
+        // d5 is attacked by both white knights, the black pawn and the black queen
+        let board = Board::from_fen("4k3/8/4p3/8/8/2N1N3/3q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.count_attackers(&move_gen, Square::D5, Color::White),
+            2
+        );
+        assert_eq!(
+            board.count_attackers(&move_gen, Square::D5, Color::Black),
+            2
+        );
+        assert_eq!(
+            board.count_attackers(&move_gen, Square::H5, Color::White),
+            0
+        );
+
+        // e2 is attacked by the white king and c3 knight, and by the black queen
+        assert_eq!(
+            board.count_attackers(&move_gen, Square::E2, Color::White),
+            2
+        );
+        assert_eq!(
+            board.count_attackers(&move_gen, Square::E2, Color::Black),
+            1
+        );
+    }
+
+    #[test]
+    fn make_move_checked_rejects_illegal_moves() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(POSITION_5, &move_gen).unwrap();
+        let original = board;
+
+        // Empty square, blocked slider, knight moving like a rook and a promotion missing
+        // its piece
+        for mv in ["a3a4", "d1d8", "e2e4", "d7c8"] {
+            let mv = Move::try_from(mv).unwrap();
+
+            assert!(board.make_move_checked(mv, &move_gen).is_err(), "{mv}");
+            assert_eq!(board, original);
+        }
+
+        let mv = Move::try_from("d7c8q").unwrap();
+
+        assert!(board.make_move_checked(mv, &move_gen).is_ok());
+    }
+
+    #[test]
+    fn board_default() {
+        assert_eq!(
             Board::from_fen(START_FEN, &MoveGen::new()).unwrap(),
             Board::default()
         );
     }
 
+    #[test]
+    fn make_move_fullmoves() {
+        let mut board = Board::default();
+
+        let e4 = board.make_move(Move::new(Square::E2, Square::E4)).unwrap();
+        assert_eq!(board.fullmoves, 1);
+
+        let e5 = board.make_move(Move::new(Square::E7, Square::E5)).unwrap();
+        assert_eq!(board.fullmoves, 2);
+
+        board.unmake_move(e5).unwrap();
+        assert_eq!(board.fullmoves, 1);
+
+        board.unmake_move(e4).unwrap();
+        assert_eq!(board.fullmoves, 1);
+    }
+
+    #[test]
+    fn white_and_black_pieces() {
+        let board = Board::default();
+
+        assert_eq!(board.white_pieces(), Bitboard(0x0000_0000_0000_FFFF));
+        assert_eq!(board.black_pieces(), Bitboard(0xFFFF_0000_0000_0000));
+        assert_eq!(
+            board.white_bitboards()[Piece::King as usize],
+            Square::E1.bitboard()
+        );
+        assert_eq!(
+            board.black_bitboards()[Piece::King as usize],
+            Square::E8.bitboard()
+        );
+    }
+
+    #[test]
+    fn color_pieces() {
+        let board = Board::default();
+
+        assert_eq!(board.color_pieces(Color::White), board.white_pieces());
+        assert_eq!(board.color_pieces(Color::Black), board.black_pieces());
+    }
+
+    #[test]
+    fn pieces_on_matches_piece_at() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        for a in Square::ALL {
+            for b in Square::ALL {
+                assert_eq!(
+                    board.pieces_on(a, b),
+                    (board.piece_at(a), board.piece_at(b))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn us_and_them() {
+        let mut board = Board::default();
+
+        assert_eq!(board.us(), Color::White);
+        assert_eq!(board.them(), Color::Black);
+        assert_eq!(board.side_to_move(), board.us());
+
+        board.flip_color();
+
+        assert_eq!(board.us(), Color::Black);
+        assert_eq!(board.them(), Color::White);
+        assert_eq!(board.side_to_move(), board.us());
+    }
+
+    #[test]
+    fn set_active_color() {
+        let mut board = Board::default();
+        let original = board;
+
+        board.set_active_color(Color::White);
+        assert_eq!(board, original);
+
+        board.set_active_color(Color::Black);
+        assert_eq!(board.us(), Color::Black);
+        assert_eq!(board.hash, board.zobrist_hash());
+        assert_ne!(board.hash, original.hash);
+
+        board.set_active_color(Color::White);
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn set_active_color_clears_en_passant() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1", &move_gen).unwrap();
+
+        board.set_active_color(Color::White);
+
+        assert_eq!(board.en_passant_square(), None);
+        assert_eq!(board.hash, board.zobrist_hash());
+        assert_eq!(board.fen(), "4k3/8/8/8/3pP3/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn is_forced() {
+        let move_gen = MoveGen::new();
+
+        // Kh7 is the only move
+        let board = Board::from_fen("7k/8/8/8/8/8/6R1/K7 b - - 0 1", &move_gen).unwrap();
+        assert!(board.is_forced(&move_gen));
+
+        // Kh7 and Kg7
+        let board = Board::from_fen("7k/8/8/8/8/8/5R2/K7 b - - 0 1", &move_gen).unwrap();
+        assert!(!board.is_forced(&move_gen));
+    }
+
+    #[test]
+    fn reversible_plies() {
+        let mut board = Board::default();
+
+        for (mv, plies) in [
+            ("g1f3", 1),
+            ("b8c6", 2),
+            ("f3e5", 3),
+            ("c6e5", 0),
+            ("b1c3", 1),
+        ] {
+            board.make_move(Move::try_from(mv).unwrap()).unwrap();
+            assert_eq!(board.reversible_plies(), plies, "{mv}");
+        }
+
+        board.make_move(Move::try_from("e7e6").unwrap()).unwrap();
+        assert_eq!(board.reversible_plies(), 0);
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut board = Board::default();
+        let mut history = Vec::new();
+
+        for _ in 0..2 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                history.push(board.hash);
+                board.make_move(Move::try_from(mv).unwrap()).unwrap();
+            }
+        }
+
+        assert_eq!(board.repetitions(&history), 2);
+
+        // After a pawn move, the earlier positions are out of reach
+        history.push(board.hash);
+        board.make_move(Move::try_from("e2e4").unwrap()).unwrap();
+
+        assert_eq!(board.repetitions(&history), 0);
+    }
+
     #[test]
     fn fen_startpos() {
         let board = Board::default();
@@ -812,6 +2146,63 @@ mod board_tests {
         assert_eq!(board.fen(), POSITION_5);
     }
 
+    #[test]
+    fn try_from_fen() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(Board::try_from(START_FEN), Ok(Board::default()));
+        assert_eq!(Board::default().to_fen(), START_FEN);
+
+        // White can capture the black king, which only the strict parser rejects
+        let fen = "7k/8/8/8/8/8/8/4K2R w - - 0 1";
+
+        assert!(Board::try_from(fen).is_ok());
+        assert_eq!(
+            Board::from_fen(fen, &move_gen),
+            Err(ParseFenError::InvalidPosition)
+        );
+
+        assert_eq!(
+            Board::try_from("8/8/8/8/8/8/8/8 w - - 0 1"),
+            Err(ParseFenError::InvalidPosition)
+        );
+        assert_eq!(
+            Board::try_from("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(ParseFenError::BadColor)
+        );
+    }
+
+    #[test]
+    fn structural_parse_then_validate() {
+        let board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+            .parse::<Board>()
+            .unwrap();
+
+        assert_eq!(board.king_square(Color::Black), Square::E8);
+
+        let move_gen = MoveGen::new();
+
+        assert_eq!(board.validate(&move_gen), Ok(()));
+
+        // Black is in check with white to move
+        let board = "4k3/8/8/8/8/8/4R3/3K4 w - - 0 1".parse::<Board>().unwrap();
+
+        assert_eq!(
+            board.validate(&move_gen),
+            Err(ParseFenError::InvalidPosition)
+        );
+    }
+
+    #[test]
+    fn fen_trailing_empty_squares() {
+        const KINGS: &str = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(KINGS, &move_gen).unwrap();
+
+        assert_eq!(board.fen(), KINGS);
+    }
+
     #[test]
     fn fen_en_passant() {
         const ONE_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
@@ -821,4 +2212,82 @@ mod board_tests {
 
         assert_eq!(board.fen(), ONE_E4);
     }
+
+    #[test]
+    fn fen_en_passant_conventions() {
+        const ONE_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        const ONE_E4_STRICT: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+
+        let move_gen = MoveGen::new();
+        let lazy = Board::from_fen(ONE_E4, &move_gen).unwrap();
+        let strict = Board::from_fen(ONE_E4_STRICT, &move_gen).unwrap();
+
+        assert_eq!(lazy.fen_with_en_passant(EnPassantConvention::Lazy), ONE_E4);
+        assert_eq!(
+            lazy.fen_with_en_passant(EnPassantConvention::Strict),
+            ONE_E4_STRICT
+        );
+        assert_eq!(
+            strict.fen_with_en_passant(EnPassantConvention::Lazy),
+            ONE_E4_STRICT
+        );
+        assert_eq!(
+            strict.fen_with_en_passant(EnPassantConvention::Strict),
+            ONE_E4_STRICT
+        );
+    }
+
+    #[test]
+    fn make_move_en_passant_not_capturable() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::default();
+        board.make_move(Move::new(Square::E2, Square::E4)).unwrap();
+
+        let expected = Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert_eq!(board.en_passant_square(), None);
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn make_move_en_passant_capturable() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::default();
+
+        for mv in [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::D7, Square::D5),
+            Move::new(Square::E4, Square::E5),
+            Move::new(Square::F7, Square::F5),
+        ] {
+            board.make_move(mv).unwrap();
+        }
+
+        let expected = Board::from_fen(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert_eq!(board.en_passant_square(), Some(Square::F6));
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn fen_en_passant_conventions_capturable() {
+        const ONE_E4: &str = "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(ONE_E4, &move_gen).unwrap();
+
+        assert_eq!(board.fen_with_en_passant(EnPassantConvention::Lazy), ONE_E4);
+        assert_eq!(
+            board.fen_with_en_passant(EnPassantConvention::Strict),
+            ONE_E4
+        );
+    }
 }
@@ -2,7 +2,12 @@ use std::{cmp::Ordering, fmt::Display};
 
 use serde::{Deserialize, Serialize};
 
-use super::{flags::Flags, piece::Piece, square::Square};
+use super::{
+    color::Color,
+    flags::{CastleSide, Flags},
+    piece::{Piece, PromotionPiece},
+    square::Square,
+};
 
 #[derive(Debug)]
 pub enum ParseMoveError {
@@ -24,6 +29,16 @@ impl Move {
 
     pub const NULLMOVE: Move = Move(0);
 
+    /// The king's move when `color` castles on `side`.
+    pub const fn castle(color: Color, side: CastleSide) -> Self {
+        match (color, side) {
+            (Color::White, CastleSide::King) => Self::KS_WHITE,
+            (Color::White, CastleSide::Queen) => Self::QS_WHITE,
+            (Color::Black, CastleSide::King) => Self::KS_BLACK,
+            (Color::Black, CastleSide::Queen) => Self::QS_BLACK,
+        }
+    }
+
     pub const fn new(from: Square, to: Square) -> Self {
         let from = from as u16;
         let to = to as u16;
@@ -33,7 +48,7 @@ impl Move {
         Self(mask)
     }
 
-    pub const fn new_with_promotion(from: Square, to: Square, promotion: Piece) -> Self {
+    pub const fn new_with_promotion(from: Square, to: Square, promotion: PromotionPiece) -> Self {
         let from = from as u16;
         let to = to as u16;
         let promotion = promotion.promotion_mask();
@@ -46,7 +61,7 @@ impl Move {
     pub const fn new_with_possible_promotion(
         from: Square,
         to: Square,
-        promotion: Option<Piece>,
+        promotion: Option<PromotionPiece>,
     ) -> Self {
         match promotion {
             Some(promotion) => Self::new_with_promotion(from, to, promotion),
@@ -64,23 +79,32 @@ impl Move {
         Square::ALL[square_index as usize]
     }
 
-    pub const fn promotion(&self) -> Option<Piece> {
-        const LOOKUP: [Option<Piece>; 9] = [
+    pub const fn promotion(&self) -> Option<PromotionPiece> {
+        const LOOKUP: [Option<PromotionPiece>; 9] = [
             None,
-            Some(Piece::Knight),
-            Some(Piece::Bishop),
+            Some(PromotionPiece::Knight),
+            Some(PromotionPiece::Bishop),
             None,
-            Some(Piece::Rook),
+            Some(PromotionPiece::Rook),
             None,
             None,
             None,
-            Some(Piece::Queen),
+            Some(PromotionPiece::Queen),
         ];
 
         let promotion_index = self.0 & Self::PROMOTION_MASK;
 
         LOOKUP[promotion_index as usize]
     }
+
+    /// The same move with both squares flipped vertically, keeping any promotion.
+    pub const fn mirror(&self) -> Move {
+        Move::new_with_possible_promotion(
+            self.from().flip_vertical(),
+            self.to().flip_vertical(),
+            self.promotion(),
+        )
+    }
 }
 
 // Only used for display purposes, does not need to be branchless
@@ -303,10 +327,12 @@ impl TryFrom<&str> for Move {
             return Err(ParseMoveError::BadTo);
         };
         let promotion = match value.chars().nth(4) {
-            Some(promotion_char) => match Piece::try_from(promotion_char) {
-                Ok(piece) => Some(piece),
-                Err(_) => return Err(ParseMoveError::BadPromotion),
-            },
+            Some(promotion_char) => {
+                match Piece::try_from(promotion_char).map(PromotionPiece::try_from) {
+                    Ok(Ok(promotion)) => Some(promotion),
+                    _ => return Err(ParseMoveError::BadPromotion),
+                }
+            }
             None => None,
         };
 
@@ -316,12 +342,12 @@ impl TryFrom<&str> for Move {
 
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let promotion_char = match self.promotion() {
-            Some(piece) => char::from(piece),
-            None => ' ',
-        };
+        write!(f, "{}{}", self.from(), self.to())?;
 
-        write!(f, "{}{}{}", self.from(), self.to(), promotion_char)
+        match self.promotion() {
+            Some(promotion) => write!(f, "{}", char::from(promotion.piece())),
+            None => Ok(()),
+        }
     }
 }
 
@@ -334,4 +360,164 @@ pub struct MoveData {
     pub flags: Flags,
 
     pub halfmoves: u32,
+
+    /// Zobrist hash of the position before the move was made.
+    pub hash: u64,
+}
+
+#[cfg(test)]
+mod move_tests {
+    use std::{
+        collections::{hash_map::DefaultHasher, BTreeSet, HashSet},
+        hash::{Hash, Hasher},
+    };
+
+    use super::*;
+
+    #[test]
+    fn castle() {
+        assert_eq!(Move::castle(Color::White, CastleSide::King), Move::KS_WHITE);
+        assert_eq!(
+            Move::castle(Color::White, CastleSide::Queen),
+            Move::QS_WHITE
+        );
+        assert_eq!(Move::castle(Color::Black, CastleSide::King), Move::KS_BLACK);
+        assert_eq!(
+            Move::castle(Color::Black, CastleSide::Queen),
+            Move::QS_BLACK
+        );
+
+        assert_eq!(Move::KS_BLACK.to_string(), "e8g8");
+    }
+
+    /// Checks every comparison method against the order of `sorted`
+    fn assert_total_order(sorted: &[Move]) {
+        for (i, a) in sorted.iter().enumerate() {
+            for (j, b) in sorted.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{a} cmp {b}");
+                assert_eq!(a.partial_cmp(b), Some(i.cmp(&j)), "{a} partial_cmp {b}");
+                assert_eq!(a < b, i < j, "{a} < {b}");
+                assert_eq!(a <= b, i <= j, "{a} <= {b}");
+                assert_eq!(a > b, i > j, "{a} > {b}");
+                assert_eq!(a >= b, i >= j, "{a} >= {b}");
+                assert_eq!(a == b, i == j, "{a} == {b}");
+            }
+        }
+    }
+
+    fn moves(moves: &[&str]) -> Vec<Move> {
+        moves
+            .iter()
+            .map(|mv| Move::try_from(*mv).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn promotions_ordered_by_piece_layout() {
+        // No promotion first, then in `Piece` order rather than by value
+        let sorted = moves(&["e7e8", "e7e8n", "e7e8b", "e7e8r", "e7e8q"]);
+
+        assert_total_order(&sorted);
+    }
+
+    #[test]
+    fn from_square_then_to_square_then_promotion() {
+        // Squares are ordered from a1 to h1, then a2 to h2 and so on
+        let sorted = moves(&["h1h2", "a2a3", "a2a4", "b7a8q", "b7b8", "b7b8n", "b7b8q"]);
+
+        assert_total_order(&sorted);
+
+        let mut shuffled = sorted.clone();
+        shuffled.reverse();
+        shuffled.swap(1, 4);
+        shuffled.sort();
+
+        assert_eq!(shuffled, sorted);
+
+        let set = sorted.iter().rev().copied().collect::<BTreeSet<Move>>();
+
+        assert!(set.into_iter().eq(sorted));
+    }
+
+    fn hash_of(mv: Move) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        mv.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn no_promotion_encodes_like_new() {
+        for from in Square::ALL {
+            for to in [Square::A1, Square::E4, Square::H8, from] {
+                let plain = Move::new(from, to);
+                let without_promotion = Move::new_with_possible_promotion(from, to, None);
+
+                assert_eq!(plain, without_promotion);
+                assert_eq!(hash_of(plain), hash_of(without_promotion));
+                assert_eq!(plain.promotion(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn promotions_are_distinct_moves() {
+        let (from, to) = (Square::E7, Square::E8);
+
+        let mut set = HashSet::from([Move::new(from, to)]);
+
+        for piece in PromotionPiece::ALL {
+            let promotion = Move::new_with_possible_promotion(from, to, Some(piece));
+
+            assert_eq!(promotion, Move::new_with_promotion(from, to, piece));
+            assert_eq!(promotion.promotion(), Some(piece));
+            assert_eq!((promotion.from(), promotion.to()), (from, to));
+            assert_ne!(promotion, Move::new(from, to));
+
+            assert!(set.insert(promotion), "{promotion}");
+        }
+
+        // The promotion bits don't overlap with the squares
+        assert_eq!(
+            Move::new_with_promotion(Square::H8, Square::H8, PromotionPiece::Queen).0,
+            Move::new(Square::H8, Square::H8).0 | PromotionPiece::Queen.promotion_mask()
+        );
+    }
+
+    #[test]
+    fn king_and_pawn_promotions_are_rejected() {
+        assert!(matches!(
+            Move::try_from("e7e8k"),
+            Err(ParseMoveError::BadPromotion)
+        ));
+        assert!(matches!(
+            Move::try_from("e7e8p"),
+            Err(ParseMoveError::BadPromotion)
+        ));
+
+        for promotion in PromotionPiece::ALL {
+            let mv = Move::new_with_promotion(Square::E7, Square::E8, promotion);
+
+            assert_eq!(Move::try_from(mv.to_string().as_str()).unwrap(), mv);
+        }
+    }
+
+    #[test]
+    fn mirror() {
+        assert_eq!(
+            Move::new(Square::E2, Square::E4).mirror(),
+            Move::new(Square::E7, Square::E5)
+        );
+        assert_eq!(
+            Move::new_with_promotion(Square::B7, Square::A8, PromotionPiece::Knight).mirror(),
+            Move::new_with_promotion(Square::B2, Square::A1, PromotionPiece::Knight)
+        );
+
+        for from in Square::ALL {
+            for to in Square::ALL {
+                let mv = Move::new(from, to);
+
+                assert_eq!(mv.mirror().mirror(), mv);
+            }
+        }
+    }
 }
@@ -0,0 +1,203 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    board::{color::Color, r#move::Move, Board},
+    move_gen::MoveGen,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IllegalMoveError {
+    /// Index of the illegal move in the line, starting from 0.
+    pub ply: usize,
+    pub r#move: Move,
+}
+
+impl Display for IllegalMoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} ({}) is illegal", self.ply + 1, self.r#move)
+    }
+}
+
+impl Error for IllegalMoveError {}
+
+/// Quotes a tag value, escaping quotes and backslashes.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Board {
+    /// Writes moves played from this position as PGN movetext with move numbers, e.g.
+    /// `1. e4 e5 2. Nf3 Nc6`. A line starting with Black's move begins with e.g. `3...`.
+    pub fn pgn_movetext(
+        &self,
+        moves: &[Move],
+        move_gen: &MoveGen,
+    ) -> Result<String, IllegalMoveError> {
+        let mut board = *self;
+        let mut tokens = Vec::new();
+
+        for (ply, &mv) in moves.iter().enumerate() {
+            let before = board;
+
+            board
+                .make_move_checked(mv, move_gen)
+                .map_err(|_| IllegalMoveError { ply, r#move: mv })?;
+
+            let move_number = before.fullmoves;
+            let color = before.active_color;
+            let san = before.move_to_san(mv, move_gen);
+
+            match color {
+                Color::White => tokens.push(format!("{move_number}.")),
+                Color::Black if ply == 0 => tokens.push(format!("{move_number}...")),
+                Color::Black => (),
+            }
+
+            tokens.push(san);
+        }
+
+        Ok(tokens.join(" "))
+    }
+
+    /// Writes a full PGN game: a tag pair for each of `tags` in order, then the movetext
+    /// and the result.
+    ///
+    /// The result is taken from the `Result` tag, or `*` without one. `SetUp` and `FEN`
+    /// tags are added when starting from anything but the start position, unless `tags`
+    /// already has a `FEN` tag.
+    pub fn to_pgn(
+        &self,
+        moves: &[Move],
+        tags: &[(&str, &str)],
+        move_gen: &MoveGen,
+    ) -> Result<String, IllegalMoveError> {
+        let movetext = self.pgn_movetext(moves, move_gen)?;
+
+        let mut pgn = String::new();
+
+        for (name, value) in tags {
+            pgn.push_str(&format!("[{name} {}]\n", quote(value)));
+        }
+
+        let has_fen = tags.iter().any(|&(name, _)| name == "FEN");
+
+        if *self != Board::default() && !has_fen {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format!("[FEN {}]\n", quote(&self.fen())));
+        }
+
+        let result = tags
+            .iter()
+            .find(|&&(name, _)| name == "Result")
+            .map_or("*", |&(_, value)| value);
+
+        if !pgn.is_empty() {
+            pgn.push('\n');
+        }
+
+        if !movetext.is_empty() {
+            pgn.push_str(&movetext);
+            pgn.push(' ');
+        }
+
+        pgn.push_str(result);
+        pgn.push('\n');
+
+        Ok(pgn)
+    }
+}
+
+#[cfg(test)]
+mod pgn_tests {
+    use super::*;
+
+    fn moves(moves: &[&str]) -> Vec<Move> {
+        moves
+            .iter()
+            .map(|&mv| Move::try_from(mv).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn movetext() {
+        let move_gen = MoveGen::new();
+        let line = moves(&["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "e1g1"]);
+
+        let movetext = Board::default().pgn_movetext(&line, &move_gen).unwrap();
+
+        assert_eq!(movetext, "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. O-O");
+        assert_eq!(
+            Board::default().parse_san_line(&movetext, &move_gen),
+            Ok(line)
+        );
+    }
+
+    #[test]
+    fn movetext_starting_with_black() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 3 3",
+            &move_gen,
+        )
+        .unwrap();
+        let line = moves(&["g8f6", "f3e5", "c6e5"]);
+
+        let movetext = board.pgn_movetext(&line, &move_gen).unwrap();
+
+        assert_eq!(movetext, "3... Nf6 4. Nxe5 Nxe5");
+        assert_eq!(board.parse_san_line(&movetext, &move_gen), Ok(line));
+    }
+
+    #[test]
+    fn illegal_move() {
+        let move_gen = MoveGen::new();
+        let line = moves(&["e2e4", "e7e5", "e1e3"]);
+
+        assert_eq!(
+            Board::default().pgn_movetext(&line, &move_gen),
+            Err(IllegalMoveError {
+                ply: 2,
+                r#move: line[2],
+            })
+        );
+    }
+
+    #[test]
+    fn full_pgn() {
+        let move_gen = MoveGen::new();
+        let line = moves(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+
+        let pgn = Board::default()
+            .to_pgn(
+                &line,
+                &[
+                    ("Event", "Fool's \"mate\""),
+                    ("Site", "?"),
+                    ("Result", "0-1"),
+                ],
+                &move_gen,
+            )
+            .unwrap();
+
+        assert_eq!(
+            pgn,
+            "[Event \"Fool's \\\"mate\\\"\"]\n[Site \"?\"]\n[Result \"0-1\"]\n\n\
+             1. f3 e5 2. g4 Qh4# 0-1\n"
+        );
+    }
+
+    #[test]
+    fn pgn_from_position() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", &move_gen).unwrap();
+
+        let pgn = board.to_pgn(&moves(&["a1a8"]), &[], &move_gen).unwrap();
+
+        assert_eq!(
+            pgn,
+            "[SetUp \"1\"]\n[FEN \"6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1\"]\n\n1. Ra8# *\n"
+        );
+
+        assert_eq!(Board::default().to_pgn(&[], &[], &move_gen).unwrap(), "*\n");
+    }
+}
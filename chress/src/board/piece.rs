@@ -2,8 +2,6 @@ use std::{error::Error, fmt::Display};
 
 use serde::{Deserialize, Serialize};
 
-use super::r#move::Move;
-
 #[derive(Debug, Clone, Copy)]
 pub struct ParsePieceCharError;
 
@@ -17,6 +15,20 @@ impl Display for ParsePieceCharError {
 
 impl Error for ParsePieceCharError {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPromotionError;
+
+impl Display for InvalidPromotionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Pawns can't promote to kings or pawns")
+    }
+}
+
+impl Error for InvalidPromotionError {}
+
+/// The derived ordering follows the layout of the enum, which is not the order of the
+/// pieces' values: pawns compare greater than queens. Use `Piece::material_rank` to
+/// compare pieces by value.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Piece {
@@ -40,15 +52,24 @@ impl Piece {
     ];
     pub const CHARS: [char; 6] = ['n', 'b', 'r', 'q', 'k', 'p'];
 
-    pub const PROMOTION_MASKS: [u16; 4] = [
-        0b0000_0000_0000_0001,
-        0b0000_0000_0000_0010,
-        0b0000_0000_0000_0100,
-        0b0000_0000_0000_1000,
-    ];
+    /// How much each piece counts towards the game phase, indexed by `Piece`. Pawns and
+    /// kings don't count, so a position with only kings and pawns has phase 0.
+    pub const PHASE_WEIGHTS: [u8; 6] = [1, 1, 2, 4, 0, 0];
 
-    pub const fn promotion_mask(&self) -> u16 {
-        (1 << *self as u16) & Move::PROMOTION_MASK
+    /// Rank of the piece by material value, from the pawn (0) to the king (5).
+    pub const fn material_rank(&self) -> u8 {
+        match self {
+            Piece::Pawn => 0,
+            Piece::Knight => 1,
+            Piece::Bishop => 2,
+            Piece::Rook => 3,
+            Piece::Queen => 4,
+            Piece::King => 5,
+        }
+    }
+
+    pub const fn phase_weight(&self) -> u8 {
+        Self::PHASE_WEIGHTS[*self as usize]
     }
 }
 
@@ -88,3 +109,144 @@ impl From<Piece> for char {
         Piece::CHARS[value as usize]
     }
 }
+
+/// A piece a pawn can promote to. Kings and pawns are left out, so a move promoting to
+/// one can't be built:
+///
+/// ```compile_fail
+/// use chress::board::{piece::Piece, r#move::Move, square::Square};
+///
+/// Move::new_with_promotion(Square::E7, Square::E8, Piece::King);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum PromotionPiece {
+    // Same order as `Piece`
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+}
+
+impl PromotionPiece {
+    pub const ALL: [PromotionPiece; 4] = [
+        PromotionPiece::Knight,
+        PromotionPiece::Bishop,
+        PromotionPiece::Rook,
+        PromotionPiece::Queen,
+    ];
+
+    pub const PROMOTION_MASKS: [u16; 4] = [
+        0b0000_0000_0000_0001,
+        0b0000_0000_0000_0010,
+        0b0000_0000_0000_0100,
+        0b0000_0000_0000_1000,
+    ];
+
+    pub const fn promotion_mask(&self) -> u16 {
+        Self::PROMOTION_MASKS[*self as usize]
+    }
+
+    pub const fn piece(&self) -> Piece {
+        Piece::ALL[*self as usize]
+    }
+}
+
+impl From<PromotionPiece> for Piece {
+    fn from(value: PromotionPiece) -> Self {
+        value.piece()
+    }
+}
+
+impl TryFrom<Piece> for PromotionPiece {
+    type Error = InvalidPromotionError;
+
+    fn try_from(value: Piece) -> Result<Self, Self::Error> {
+        match value {
+            Piece::Knight => Ok(PromotionPiece::Knight),
+            Piece::Bishop => Ok(PromotionPiece::Bishop),
+            Piece::Rook => Ok(PromotionPiece::Rook),
+            Piece::Queen => Ok(PromotionPiece::Queen),
+            Piece::King | Piece::Pawn => Err(InvalidPromotionError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod piece_tests {
+    use super::*;
+    use crate::board::{color::Color, r#move::Move, Board};
+
+    #[test]
+    fn layout_order_is_not_material_order() {
+        let mut by_layout = Piece::ALL;
+        by_layout.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut by_material = Piece::ALL;
+        by_material.sort_by_key(Piece::material_rank);
+
+        assert_eq!(by_layout, Piece::ALL);
+        assert_eq!(
+            by_material,
+            [
+                Piece::Pawn,
+                Piece::Knight,
+                Piece::Bishop,
+                Piece::Rook,
+                Piece::Queen,
+                Piece::King,
+            ]
+        );
+
+        assert!(Piece::Pawn > Piece::Queen);
+        assert!(Piece::Pawn.material_rank() < Piece::Queen.material_rank());
+    }
+
+    #[test]
+    fn start_position_phase() {
+        let board = Board::default();
+
+        let phase: u32 = Piece::ALL
+            .iter()
+            .flat_map(|&piece| Color::ALL.map(|color| (piece, color)))
+            .map(|(piece, color)| {
+                board.bitboard(piece, color).0.count_ones() * piece.phase_weight() as u32
+            })
+            .sum();
+
+        assert_eq!(phase, 24);
+    }
+
+    #[test]
+    fn promotion_pieces() {
+        for promotion in PromotionPiece::ALL {
+            assert_eq!(PromotionPiece::try_from(promotion.piece()), Ok(promotion));
+        }
+
+        assert_eq!(
+            PromotionPiece::try_from(Piece::King),
+            Err(InvalidPromotionError)
+        );
+        assert_eq!(
+            PromotionPiece::try_from(Piece::Pawn),
+            Err(InvalidPromotionError)
+        );
+    }
+
+    #[test]
+    fn promotion_masks_are_distinct_and_nonzero() {
+        let mut seen = 0;
+
+        for promotion in PromotionPiece::ALL {
+            let mask = promotion.promotion_mask();
+
+            assert_eq!(mask.count_ones(), 1, "{promotion:?}");
+            assert_eq!(mask & !Move::PROMOTION_MASK, 0, "{promotion:?}");
+            assert_eq!(seen & mask, 0, "{promotion:?}");
+
+            seen |= mask;
+        }
+
+        assert_eq!(seen, Move::PROMOTION_MASK);
+    }
+}
@@ -0,0 +1,635 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    board::{
+        flags::CastleSide,
+        piece::{Piece, PromotionPiece},
+        r#move::Move,
+        square::Square,
+        Board,
+    },
+    move_gen::MoveGen,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseSanError {
+    Empty,
+    BadSquare,
+    BadPromotion,
+    BadDisambiguation,
+    NoMatchingMove,
+    AmbiguousMove,
+}
+
+impl Display for ParseSanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Empty => "empty move",
+            Self::BadSquare => "missing or invalid target square",
+            Self::BadPromotion => "invalid promotion piece",
+            Self::BadDisambiguation => "invalid disambiguation",
+            Self::NoMatchingMove => "no legal move matches",
+            Self::AmbiguousMove => "more than one legal move matches",
+        };
+
+        write!(f, "{message}")
+    }
+}
+
+impl Error for ParseSanError {}
+
+/// A move in a SAN movetext which couldn't be applied.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ApplySanError {
+    /// Index of the failing move in the line, starting from 0.
+    pub ply: usize,
+    pub san: String,
+    pub error: ParseSanError,
+}
+
+impl Display for ApplySanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} ('{}'): {}", self.ply + 1, self.san, self.error)
+    }
+}
+
+impl Error for ApplySanError {}
+
+const RESULT_TOKENS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// Removes a leading move number (`1.`, `12...`) from a movetext token.
+fn strip_move_number(token: &str) -> &str {
+    let without_digits = token.trim_start_matches(|ch: char| ch.is_ascii_digit());
+
+    if without_digits.len() < token.len() && without_digits.starts_with('.') {
+        without_digits.trim_start_matches('.')
+    } else {
+        token
+    }
+}
+
+fn piece_from_san_char(ch: char) -> Option<Piece> {
+    match ch {
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+fn file_from_char(ch: char) -> Option<u8> {
+    matches!(ch, 'a'..='h').then(|| ch as u8 - b'a')
+}
+
+fn rank_from_char(ch: char) -> Option<u8> {
+    matches!(ch, '1'..='8').then(|| ch as u8 - b'1')
+}
+
+impl Board {
+    /// Writes a legal move in Standard Algebraic Notation (e.g. `Nf3`, `exd5`, `O-O`,
+    /// `e8=Q`, `Qxe7+`).
+    ///
+    /// Moves from an empty square are written in long algebraic notation instead.
+    pub fn move_to_san(&self, mv: Move, move_gen: &MoveGen) -> String {
+        let from = mv.from();
+        let to = mv.to();
+
+        let Some(piece) = self.piece_at(from) else {
+            return mv.to_string();
+        };
+
+        let mut legal_moves = Vec::new();
+        move_gen.legal_moves(self, &mut legal_moves);
+
+        let mut san = String::new();
+
+        if piece == Piece::King && from.file().abs_diff(to.file()) == 2 {
+            san.push_str(if to.file() == 6 { "O-O" } else { "O-O-O" });
+        } else {
+            let is_en_passant = piece == Piece::Pawn && self.en_passant_square() == Some(to);
+            let is_capture = self.piece_at(to).is_some() || is_en_passant;
+
+            if piece == Piece::Pawn {
+                if is_capture {
+                    san.push((b'a' + from.file()) as char);
+                }
+            } else {
+                san.push(char::from(piece).to_ascii_uppercase());
+
+                // Other pieces of the same type which can reach the same square
+                let others = legal_moves
+                    .iter()
+                    .map(|other| other.from())
+                    .filter(|&other| {
+                        other != from
+                            && self.piece_at(other) == Some(piece)
+                            && legal_moves.contains(&Move::new(other, to))
+                    })
+                    .collect::<Vec<Square>>();
+
+                if !others.is_empty() {
+                    let shares_file = others.iter().any(|other| other.file() == from.file());
+                    let shares_rank = others.iter().any(|other| other.rank() == from.rank());
+
+                    if !shares_file {
+                        san.push((b'a' + from.file()) as char);
+                    } else if !shares_rank {
+                        san.push((b'1' + from.rank()) as char);
+                    } else {
+                        san.push_str(&from.to_string());
+                    }
+                }
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+
+            san.push_str(&to.to_string());
+
+            if let Some(promotion) = mv.promotion() {
+                san.push('=');
+                san.push(char::from(promotion.piece()).to_ascii_uppercase());
+            }
+        }
+
+        // Check and checkmate
+        let mut board = *self;
+        if board.make_move(mv).is_ok() && board.is_in_check(move_gen) {
+            let mut replies = Vec::new();
+            move_gen.legal_moves(&board, &mut replies);
+
+            san.push(if replies.is_empty() { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    /// Parses a line of SAN moves (e.g. `1. e4 e5 2. Nf3 Nc6`) into the moves it plays.
+    /// Move numbers and game result tokens are skipped.
+    pub fn parse_san_line(
+        &self,
+        movetext: &str,
+        move_gen: &MoveGen,
+    ) -> Result<Vec<Move>, ApplySanError> {
+        let mut board = *self;
+        let mut moves = Vec::new();
+
+        let tokens = movetext
+            .split_ascii_whitespace()
+            .filter(|token| !RESULT_TOKENS.contains(token))
+            .map(strip_move_number)
+            .filter(|token| !token.is_empty());
+
+        for (ply, san) in tokens.enumerate() {
+            let mv = board
+                .parse_san(san, move_gen)
+                .map_err(|error| ApplySanError {
+                    ply,
+                    san: san.to_owned(),
+                    error,
+                })?;
+
+            // Moves from parse_san are always legal
+            board.make_move(mv).unwrap();
+            moves.push(mv);
+        }
+
+        Ok(moves)
+    }
+
+    /// Applies a line of SAN moves (e.g. `1. e4 e5 2. Nf3 Nc6`) and returns the resulting
+    /// board. Move numbers and game result tokens are skipped.
+    pub fn apply_san_line(
+        &self,
+        movetext: &str,
+        move_gen: &MoveGen,
+    ) -> Result<Board, ApplySanError> {
+        let moves = self.parse_san_line(movetext, move_gen)?;
+
+        let mut board = *self;
+        board.make_moves(&moves).unwrap();
+
+        Ok(board)
+    }
+
+    /// Parses a move written in Standard Algebraic Notation (e.g. `Nf3`, `exd5`, `O-O`,
+    /// `e8=Q+`) into the matching legal move. Check and mate markers and annotations like
+    /// `!?` are ignored.
+    pub fn parse_san(&self, san: &str, move_gen: &MoveGen) -> Result<Move, ParseSanError> {
+        let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+        if san.is_empty() {
+            return Err(ParseSanError::Empty);
+        }
+
+        let mut legal_moves = Vec::new();
+        move_gen.legal_moves(self, &mut legal_moves);
+
+        // Castling
+        let castling_side = match san {
+            "O-O" | "0-0" => Some(CastleSide::King),
+            "O-O-O" | "0-0-0" => Some(CastleSide::Queen),
+            _ => None,
+        };
+
+        if let Some(side) = castling_side {
+            let mv = Move::castle(self.active_color, side);
+
+            let is_castling = self.piece_at(mv.from()) == Some(Piece::King);
+
+            return if is_castling && legal_moves.contains(&mv) {
+                Ok(mv)
+            } else {
+                Err(ParseSanError::NoMatchingMove)
+            };
+        }
+
+        let mut chars = san.chars().collect::<Vec<char>>();
+
+        let piece = match piece_from_san_char(chars[0]) {
+            Some(piece) => {
+                chars.remove(0);
+                piece
+            }
+            None => Piece::Pawn,
+        };
+
+        // Promotions may be written with or without the '='
+        let promotion = match chars.last().cloned().and_then(piece_from_san_char) {
+            Some(piece) => {
+                let Ok(promotion) = PromotionPiece::try_from(piece) else {
+                    return Err(ParseSanError::BadPromotion);
+                };
+
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(promotion)
+            }
+            None => None,
+        };
+
+        // Target square
+        if chars.len() < 2 {
+            return Err(ParseSanError::BadSquare);
+        }
+
+        let rank = chars.pop().and_then(rank_from_char);
+        let file = chars.pop().and_then(file_from_char);
+
+        let (Some(rank), Some(file)) = (rank, file) else {
+            return Err(ParseSanError::BadSquare);
+        };
+
+        let to = Square::ALL[(rank * 8 + file) as usize];
+
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        // Whatever remains is the disambiguation
+        let mut from_file = None;
+        let mut from_rank = None;
+
+        for ch in chars {
+            if let Some(file) = file_from_char(ch) {
+                from_file = Some(file);
+            } else if let Some(rank) = rank_from_char(ch) {
+                from_rank = Some(rank);
+            } else {
+                return Err(ParseSanError::BadDisambiguation);
+            }
+        }
+
+        let mut matching = legal_moves.into_iter().filter(|mv| {
+            mv.to() == to
+                && mv.promotion() == promotion
+                && self.piece_at(mv.from()) == Some(piece)
+                && from_file.is_none_or(|file| mv.from().file() == file)
+                && from_rank.is_none_or(|rank| mv.from().rank() == rank)
+        });
+
+        let Some(mv) = matching.next() else {
+            return Err(ParseSanError::NoMatchingMove);
+        };
+
+        if matching.next().is_some() {
+            return Err(ParseSanError::AmbiguousMove);
+        }
+
+        Ok(mv)
+    }
+}
+
+#[cfg(test)]
+mod san_tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn move_to_san() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+        let cases = [
+            (Move::KS_WHITE, "O-O"),
+            (Move::QS_WHITE, "O-O-O"),
+            (Move::new(Square::D5, Square::E6), "dxe6"),
+            (Move::new(Square::F3, Square::F6), "Qxf6"),
+            (Move::new(Square::E5, Square::F7), "Nxf7"),
+            (Move::new(Square::C3, Square::B5), "Nb5"),
+            (Move::new(Square::A2, Square::A4), "a4"),
+        ];
+
+        for (mv, san) in cases {
+            assert_eq!(board.move_to_san(mv, &move_gen), san);
+        }
+    }
+
+    #[test]
+    fn move_to_san_kiwipete_black() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        let cases = [
+            (Move::KS_BLACK, "O-O"),
+            (Move::QS_BLACK, "O-O-O"),
+            (Move::new(Square::B6, Square::D5), "Nbxd5"),
+            (Move::new(Square::F6, Square::D5), "Nfxd5"),
+            (Move::new(Square::F6, Square::E4), "Nxe4"),
+            (Move::new(Square::A6, Square::E2), "Bxe2"),
+            (Move::new(Square::B4, Square::C3), "bxc3"),
+            (Move::new(Square::H3, Square::G2), "hxg2"),
+            (Move::new(Square::B4, Square::B3), "b3"),
+        ];
+
+        for (mv, san) in cases {
+            assert_eq!(board.move_to_san(mv, &move_gen), san);
+            assert_eq!(board.parse_san(san, &move_gen), Ok(mv));
+        }
+    }
+
+    #[test]
+    fn move_to_san_check_and_mate() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.move_to_san(Move::new(Square::A1, Square::A8), &move_gen),
+            "Ra8#"
+        );
+        assert_eq!(
+            board.move_to_san(Move::new(Square::E1, Square::E2), &move_gen),
+            "Ke2"
+        );
+
+        let board = Board::from_fen("6k1/8/8/8/8/8/8/R3K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.move_to_san(Move::new(Square::A1, Square::A8), &move_gen),
+            "Ra8+"
+        );
+    }
+
+    #[test]
+    fn parse_san_startpos() {
+        let move_gen = MoveGen::new();
+        let board = Board::default();
+
+        assert_eq!(
+            board.parse_san("e4", &move_gen),
+            Ok(Move::new(Square::E2, Square::E4))
+        );
+        assert_eq!(
+            board.parse_san("Nf3", &move_gen),
+            Ok(Move::new(Square::G1, Square::F3))
+        );
+        assert_eq!(
+            board.parse_san("Nf6", &move_gen),
+            Err(ParseSanError::NoMatchingMove)
+        );
+        assert_eq!(board.parse_san("", &move_gen), Err(ParseSanError::Empty));
+        assert_eq!(
+            board.parse_san("Nz9", &move_gen),
+            Err(ParseSanError::BadSquare)
+        );
+    }
+
+    #[test]
+    fn parse_san_kiwipete() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+        assert_eq!(board.parse_san("O-O", &move_gen), Ok(Move::KS_WHITE));
+        assert_eq!(board.parse_san("O-O-O", &move_gen), Ok(Move::QS_WHITE));
+        assert_eq!(
+            board.parse_san("dxe6", &move_gen),
+            Ok(Move::new(Square::D5, Square::E6))
+        );
+        assert_eq!(
+            board.parse_san("Qxf6", &move_gen),
+            Ok(Move::new(Square::F3, Square::F6))
+        );
+        assert_eq!(
+            board.parse_san("Nxf7", &move_gen),
+            Ok(Move::new(Square::E5, Square::F7))
+        );
+        assert_eq!(
+            board.parse_san("gxh3", &move_gen),
+            Ok(Move::new(Square::G2, Square::H3))
+        );
+    }
+
+    #[test]
+    fn apply_san_line() {
+        let move_gen = MoveGen::new();
+        let board = Board::default()
+            .apply_san_line("1. e4 e5 2. Nf3 Nc6 3.Bb5 a6 4. O-O *", &move_gen)
+            .unwrap();
+
+        assert_eq!(
+            board.fen(),
+            "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 1 4"
+        );
+
+        let board = Board::default()
+            .apply_san_line("1. d4 d5 2. c4 2... e6 1/2-1/2", &move_gen)
+            .unwrap();
+
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/ppp2ppp/4p3/3p4/2PP4/8/PP2PPPP/RNBQKBNR w KQkq - 0 3"
+        );
+    }
+
+    #[test]
+    fn apply_san_line_error() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(
+            Board::default().apply_san_line("1. e4 e5 2. Ke3", &move_gen),
+            Err(ApplySanError {
+                ply: 2,
+                san: "Ke3".to_owned(),
+                error: ParseSanError::NoMatchingMove,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_san_ignores_annotations() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(KIWIPETE, &move_gen).unwrap();
+
+        for san in ["Nxf7!", "Nxf7?", "Nxf7!!", "Nxf7?!", "Nxf7+!?"] {
+            assert_eq!(
+                board.parse_san(san, &move_gen),
+                Ok(Move::new(Square::E5, Square::F7)),
+                "{san}"
+            );
+        }
+
+        assert_eq!(board.parse_san("O-O!", &move_gen), Ok(Move::KS_WHITE));
+        assert_eq!(board.parse_san("!?", &move_gen), Err(ParseSanError::Empty));
+    }
+
+    #[test]
+    fn parse_san_disambiguation() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.parse_san("Rd1", &move_gen),
+            Err(ParseSanError::AmbiguousMove)
+        );
+        assert_eq!(
+            board.parse_san("Rad1", &move_gen),
+            Ok(Move::new(Square::A1, Square::D1))
+        );
+        assert_eq!(
+            board.parse_san("Rhf1", &move_gen),
+            Ok(Move::new(Square::H1, Square::F1))
+        );
+    }
+
+    #[test]
+    fn parse_san_promotion() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.parse_san("e8=Q+", &move_gen),
+            Ok(Move::new_with_promotion(
+                Square::E7,
+                Square::E8,
+                PromotionPiece::Queen
+            ))
+        );
+        assert_eq!(
+            board.parse_san("exd8N", &move_gen),
+            Ok(Move::new_with_promotion(
+                Square::E7,
+                Square::D8,
+                PromotionPiece::Knight
+            ))
+        );
+        assert_eq!(
+            board.parse_san("e8=K", &move_gen),
+            Err(ParseSanError::BadPromotion)
+        );
+    }
+}
+
+#[cfg(test)]
+mod san_disambiguation_tests {
+    use super::*;
+
+    fn assert_san(fen: &str, mv: Move, san: &str) {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(fen, &move_gen).unwrap();
+
+        assert_eq!(board.move_to_san(mv, &move_gen), san);
+        assert_eq!(board.parse_san(san, &move_gen), Ok(mv));
+    }
+
+    #[test]
+    fn file_disambiguation() {
+        let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+
+        assert_san(fen, Move::new(Square::B1, Square::D2), "Nbd2");
+        assert_san(fen, Move::new(Square::F1, Square::D2), "Nfd2");
+    }
+
+    #[test]
+    fn rank_disambiguation() {
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+
+        assert_san(fen, Move::new(Square::A1, Square::A3), "R1a3");
+        assert_san(fen, Move::new(Square::A5, Square::A3), "R5a3");
+    }
+
+    #[test]
+    fn square_disambiguation() {
+        let fen = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+
+        assert_san(fen, Move::new(Square::A1, Square::B2), "Qa1b2");
+        assert_san(fen, Move::new(Square::A3, Square::B2), "Q3b2");
+        assert_san(fen, Move::new(Square::C1, Square::B2), "Qcb2");
+    }
+
+    #[test]
+    fn capture_disambiguation() {
+        let fen = "4k3/8/8/8/8/8/3p4/1N3N1K w - - 0 1";
+
+        assert_san(fen, Move::new(Square::B1, Square::D2), "Nbxd2");
+        assert_san(fen, Move::new(Square::F1, Square::D2), "Nfxd2");
+    }
+
+    #[test]
+    fn pinned_piece_needs_no_disambiguation() {
+        // The knight on d2 is pinned, so only the knight on g1 can go to f3
+        let fen = "4k3/8/8/b7/8/8/3N4/4K1N1 w - - 0 1";
+
+        assert_san(fen, Move::new(Square::G1, Square::F3), "Nf3");
+    }
+
+    #[test]
+    fn promotions_to_san() {
+        let move_gen = MoveGen::new();
+
+        // The d7 pawn can promote by pushing or by taking either rook
+        let board = Board::from_fen("2r1r3/3P2k1/8/8/8/8/8/K7 w - - 0 1", &move_gen).unwrap();
+
+        let mut moves = Vec::new();
+        move_gen.legal_moves(&board, &mut moves);
+
+        let mut promotions = moves
+            .into_iter()
+            .filter(|mv| mv.promotion().is_some())
+            .map(|mv| board.move_to_san(mv, &move_gen))
+            .collect::<Vec<String>>();
+        promotions.sort();
+
+        assert_eq!(
+            promotions,
+            [
+                "d8=B", "d8=N", "d8=Q", "d8=R", "dxc8=B", "dxc8=N", "dxc8=Q", "dxc8=R", "dxe8=B",
+                "dxe8=N+", "dxe8=Q", "dxe8=R",
+            ]
+        );
+
+        for san in ["d8=Q", "dxe8=N+", "dxc8=R"] {
+            let mv = board.parse_san(san, &move_gen).unwrap();
+            assert_eq!(board.move_to_san(mv, &move_gen), san);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::cmp::Reverse;
+
+use crate::{
+    board::{piece::Piece, r#move::Move, square::Square, Board},
+    move_gen::MoveGen,
+};
+
+/// Piece values used by static exchange evaluation, indexed by `Piece`.
+pub const SEE_VALUES: [i32; 6] = [320, 330, 500, 900, 20000, 100];
+
+/// Pieces from least to most valuable, which is the order they join an exchange in.
+const EXCHANGE_ORDER: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+impl Board {
+    /// Static exchange evaluation: the material balance for the side to move after the
+    /// move and every following recapture on the same square, assuming both sides always
+    /// recapture with their least valuable piece and stop when recapturing loses material.
+    ///
+    /// Non-captures are evaluated as if the moved piece could be captured on its target
+    /// square, so a safe quiet move scores 0.
+    pub fn see(&self, move_gen: &MoveGen, mv: Move) -> i32 {
+        let from = mv.from();
+        let to = mv.to();
+
+        let Some(attacker) = self.piece_at(from) else {
+            return 0;
+        };
+
+        let is_en_passant = attacker == Piece::Pawn && self.en_passant_square() == Some(to);
+
+        let mut occupied = self.occupied() ^ from.bitboard();
+        let mut gain = [0; 32];
+
+        gain[0] = match self.piece_at(to) {
+            Some(captured) => SEE_VALUES[captured as usize],
+            None if is_en_passant => {
+                // Remove the captured pawn, which isn't on the target square
+                let captured_square = to as usize ^ 8;
+                occupied ^= Square::ALL[captured_square].bitboard();
+                SEE_VALUES[Piece::Pawn as usize]
+            }
+            None => 0,
+        };
+
+        let mut piece_value = SEE_VALUES[attacker as usize];
+
+        if let Some(promotion) = mv.promotion().map(Piece::from) {
+            gain[0] += SEE_VALUES[promotion as usize] - SEE_VALUES[Piece::Pawn as usize];
+            piece_value = SEE_VALUES[promotion as usize];
+        }
+
+        let mut color = self.them();
+        let mut attackers = move_gen.attackers_to(self, to, occupied) & occupied;
+        let mut depth = 0;
+
+        loop {
+            let side_attackers = attackers & self.color_pieces(color);
+
+            let Some((piece, square)) = EXCHANGE_ORDER.iter().find_map(|&piece| {
+                let pieces = side_attackers & self.bitboard(piece, color);
+                (!pieces.is_empty()).then(|| (piece, pieces.0.trailing_zeros()))
+            }) else {
+                break;
+            };
+
+            depth += 1;
+            gain[depth] = piece_value - gain[depth - 1];
+            piece_value = SEE_VALUES[piece as usize];
+
+            // Removing the capturing piece may reveal sliders behind it
+            occupied.0 ^= 1 << square;
+            attackers = move_gen.attackers_to(self, to, occupied) & occupied;
+
+            color = color.inverse();
+        }
+
+        // Either side may choose not to recapture
+        while depth > 0 {
+            gain[depth - 1] = -i32::max(-gain[depth - 1], gain[depth]);
+            depth -= 1;
+        }
+
+        gain[0]
+    }
+
+    /// Checks whether the static exchange evaluation of a move is at least `threshold`.
+    pub fn see_ge(&self, move_gen: &MoveGen, mv: Move, threshold: i32) -> bool {
+        self.see(move_gen, mv) >= threshold
+    }
+
+    /// Checks whether a move captures a piece without losing material in the following
+    /// exchange, by static exchange evaluation. Moves which don't capture return false.
+    pub fn is_safe_capture(&self, move_gen: &MoveGen, mv: Move) -> bool {
+        self.is_capture(mv) && self.see_ge(move_gen, mv, 0)
+    }
+
+    /// Returns the legal capture with the highest static exchange evaluation along with
+    /// its score, or `None` if there are no captures. Ties go to the first capture
+    /// generated.
+    pub fn best_capture(&self, move_gen: &MoveGen) -> Option<(Move, i32)> {
+        let mut moves = Vec::new();
+        move_gen.legal_moves(self, &mut moves);
+
+        let mut best: Option<(Move, i32)> = None;
+
+        for mv in moves.into_iter().filter(|&mv| self.is_capture(mv)) {
+            let score = self.see(move_gen, mv);
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((mv, score));
+            }
+        }
+
+        best
+    }
+
+    fn is_capture(&self, mv: Move) -> bool {
+        let is_en_passant = self.piece_at(mv.from()) == Some(Piece::Pawn)
+            && self.en_passant_square() == Some(mv.to());
+
+        !(self.enemy_pieces() & mv.to().bitboard()).is_empty() || is_en_passant
+    }
+
+    /// Splits captures into those which win or break even by static exchange evaluation,
+    /// and those which lose material. Both keep the order of `moves`.
+    pub fn partition_captures(&self, move_gen: &MoveGen, moves: &[Move]) -> (Vec<Move>, Vec<Move>) {
+        moves.iter().partition(|&&mv| self.see_ge(move_gen, mv, 0))
+    }
+
+    /// Sorts moves by static exchange evaluation, best first.
+    ///
+    /// The sort is stable, so moves with equal scores keep their relative order.
+    pub fn order_captures(&self, move_gen: &MoveGen, moves: &mut [Move]) {
+        moves.sort_by_cached_key(|&mv| Reverse(self.see(move_gen, mv)));
+    }
+}
+
+#[cfg(test)]
+mod see_tests {
+    use crate::board::square::Square;
+
+    use super::*;
+
+    #[test]
+    fn see_undefended() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("4k3/8/3b4/8/4N3/8/8/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(board.see(&move_gen, Move::new(Square::E4, Square::D6)), 330);
+    }
+
+    #[test]
+    fn see_defended() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("4k3/2p5/3p4/8/8/8/3Q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.see(&move_gen, Move::new(Square::D2, Square::D6)),
+            -800
+        );
+        assert!(!board.see_ge(&move_gen, Move::new(Square::D2, Square::D6), 0));
+    }
+
+    #[test]
+    fn see_x_ray() {
+        let move_gen = MoveGen::new();
+
+        // The rook behind the queen makes the exchange on d5 favourable
+        let board = Board::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(board.see(&move_gen, Move::new(Square::D2, Square::D5)), 100);
+
+        let board = Board::from_fen("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.see(&move_gen, Move::new(Square::D2, Square::D5)),
+            -400
+        );
+    }
+
+    #[test]
+    fn order_captures() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("4k3/2p5/3p4/8/4N1b1/8/3Q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        let losing = Move::new(Square::D2, Square::D6);
+        let winning = Move::new(Square::D2, Square::G5);
+        let mut moves = vec![losing, Move::new(Square::E4, Square::D6), winning];
+
+        board.order_captures(&move_gen, &mut moves);
+
+        assert_eq!(
+            moves,
+            vec![winning, Move::new(Square::E4, Square::D6), losing]
+        );
+    }
+
+    #[test]
+    fn partition_captures() {
+        let move_gen = MoveGen::new();
+        let board =
+            Board::from_fen("4k3/2p5/3p4/2n5/4N1b1/5P2/3Q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        let mut moves = Vec::new();
+        move_gen.legal_moves(&board, &mut moves);
+        moves.retain(|mv| !(board.enemy_pieces() & mv.to().bitboard()).is_empty());
+
+        let (good, bad) = board.partition_captures(&move_gen, &moves);
+
+        let names = |moves: Vec<Move>| moves.iter().map(Move::to_string).collect::<Vec<String>>();
+
+        // Winning the bishop and trading knights, then losing the queen or knight for a pawn
+        assert_eq!(names(good), ["f3g4", "e4c5"]);
+        assert_eq!(names(bad), ["e4d6", "d2d6"]);
+    }
+
+    #[test]
+    fn is_safe_capture() {
+        let move_gen = MoveGen::new();
+
+        // The pawn on d6 is defended, the bishop on g5 isn't
+        let board = Board::from_fen("4k3/2p5/3p4/6b1/8/8/3Q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert!(!board.is_safe_capture(&move_gen, Move::new(Square::D2, Square::D6)));
+        assert!(board.is_safe_capture(&move_gen, Move::new(Square::D2, Square::G5)));
+
+        // Quiet moves aren't captures, even when they are safe
+        assert!(!board.is_safe_capture(&move_gen, Move::new(Square::D2, Square::D3)));
+    }
+
+    #[test]
+    fn best_capture() {
+        let move_gen = MoveGen::new();
+
+        // Taking the undefended bishop beats taking the defended pawn on d6
+        let board = Board::from_fen("4k3/2p5/3p4/6b1/8/8/3Q4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        assert_eq!(
+            board.best_capture(&move_gen),
+            Some((Move::new(Square::D2, Square::G5), 330))
+        );
+
+        assert_eq!(Board::default().best_capture(&move_gen), None);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::board::Board;
+
+/// A saved copy of a board's full state, which can be restored later without keeping
+/// track of the `MoveData` of every move made in between.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardSnapshot {
+    board: Board,
+}
+
+impl Board {
+    pub fn snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot { board: *self }
+    }
+
+    /// Restores the board to the state it was in when `snapshot` was taken.
+    pub fn restore(&mut self, snapshot: BoardSnapshot) {
+        *self = snapshot.board;
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::{board::r#move::Move, move_gen::MoveGen};
+
+    #[test]
+    fn snapshot_round_trips() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        let original = board;
+        let snapshot = board.snapshot();
+
+        for mv in ["e1g1", "b4c3", "d2c3", "e8c8"] {
+            board
+                .make_move_checked(Move::try_from(mv).unwrap(), &move_gen)
+                .unwrap();
+        }
+
+        assert_ne!(board, original);
+
+        board.restore(snapshot);
+
+        assert_eq!(board, original);
+    }
+}
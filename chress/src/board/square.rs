@@ -141,17 +141,30 @@ impl Square {
         Square::H8,
     ];
 
+    #[inline]
     pub const fn bitboard(&self) -> Bitboard {
         Bitboard(1 << *self as u8)
     }
 
+    #[inline]
     pub const fn rank(&self) -> u8 {
         *self as u8 / 8
     }
 
+    #[inline]
     pub const fn file(&self) -> u8 {
         *self as u8 % 8
     }
+
+    /// Whether the square is a light square, like h1.
+    pub const fn is_light(&self) -> bool {
+        (self.rank() + self.file()) % 2 == 1
+    }
+
+    /// The square on the same file, mirrored across the middle of the board.
+    pub const fn flip_vertical(&self) -> Square {
+        Self::ALL[*self as usize ^ 56]
+    }
 }
 
 impl Display for Square {
@@ -205,3 +218,29 @@ impl TryFrom<Bitboard> for Square {
         Self::try_from(square_index)
     }
 }
+
+#[cfg(test)]
+mod square_tests {
+    use super::*;
+
+    #[test]
+    fn is_light() {
+        assert!(!Square::A1.is_light());
+        assert!(Square::H1.is_light());
+        assert!(Square::D1.is_light());
+        assert!(!Square::D8.is_light());
+        assert!(!Square::H8.is_light());
+    }
+
+    #[test]
+    fn flip_vertical() {
+        assert_eq!(Square::A1.flip_vertical(), Square::A8);
+        assert_eq!(Square::E2.flip_vertical(), Square::E7);
+        assert_eq!(Square::H5.flip_vertical(), Square::H4);
+
+        for square in Square::ALL {
+            assert_eq!(square.flip_vertical().file(), square.file());
+            assert_eq!(square.flip_vertical().flip_vertical(), square);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use crate::board::{color::Color, flags::Flags, piece::Piece, square::Square, Board};
+
+/// Keys for each piece on each square, indexed the same way as `Board::pieces`.
+pub const PIECE_KEYS: [[u64; 64]; 12] = {
+    let mut keys = [[0; 64]; 12];
+    let mut state = SEED;
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            state = xorshift(state);
+            keys[piece][square] = state;
+            square += 1;
+        }
+        piece += 1;
+    }
+
+    keys
+};
+
+/// Keys for each castling right, in the same bit order as `Flags`.
+pub const CASTLING_KEYS: [u64; 4] = {
+    let mut keys = [0; 4];
+    let mut state = xorshift(PIECE_KEYS[11][63]);
+
+    let mut i = 0;
+    while i < 4 {
+        keys[i] = state;
+        state = xorshift(state);
+        i += 1;
+    }
+
+    keys
+};
+
+/// Keys for each en passant file.
+pub const EN_PASSANT_KEYS: [u64; 8] = {
+    let mut keys = [0; 8];
+    let mut state = xorshift(CASTLING_KEYS[3]);
+
+    let mut i = 0;
+    while i < 8 {
+        keys[i] = state;
+        state = xorshift(state);
+        i += 1;
+    }
+
+    keys
+};
+
+/// Key toggled when it is Black's turn to move.
+pub const BLACK_TO_MOVE_KEY: u64 = xorshift(EN_PASSANT_KEYS[7]);
+
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+const fn xorshift(mut state: u64) -> u64 {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state
+}
+
+/// Combined key of every castling right set in `flags`.
+///
+/// Because the keys are XORed together, the key of the rights which changed between two
+/// sets of flags is `castling_key(Flags(old.0 ^ new.0))`.
+pub const fn castling_key(flags: Flags) -> u64 {
+    let mut key = 0;
+
+    let mut i = 0;
+    while i < 4 {
+        if flags.0 & (1 << i) != 0 {
+            key ^= CASTLING_KEYS[i];
+        }
+        i += 1;
+    }
+
+    key
+}
+
+/// Key of the en passant square in `flags`, or 0 if en passant isn't valid.
+///
+/// Positions only include it in their hash if en passant can be captured, see
+/// `Board::zobrist_hash`.
+pub fn en_passant_key(flags: Flags) -> u64 {
+    match flags.en_passant_file() {
+        Some(file) => EN_PASSANT_KEYS[file as usize],
+        None => 0,
+    }
+}
+
+pub fn piece_key(piece: Piece, color: Color, square: Square) -> u64 {
+    PIECE_KEYS[piece as usize + color as usize * 6][square as usize]
+}
+
+impl Board {
+    /// Computes the Zobrist hash of the position from scratch. Castling rights and the
+    /// en passant file are part of the key, but en passant only when it can be captured.
+    ///
+    /// `Board::hash` holds the same value, kept up to date incrementally.
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for (index, bitboard) in self.pieces.iter().enumerate() {
+            let mut bitboard = *bitboard;
+
+            while !bitboard.is_empty() {
+                hash ^= PIECE_KEYS[index][bitboard.pop_lsb() as usize];
+            }
+        }
+
+        hash ^= castling_key(self.flags);
+        hash ^= self.en_passant_hash();
+
+        if self.active_color == Color::Black {
+            hash ^= BLACK_TO_MOVE_KEY;
+        }
+
+        hash
+    }
+}
+
+#[cfg(test)]
+mod zobrist_tests {
+    use crate::{board::r#move::Move, move_gen::MoveGen};
+
+    use super::*;
+
+    #[test]
+    fn keys_are_unique() {
+        let mut keys = PIECE_KEYS.concat();
+        keys.extend(CASTLING_KEYS);
+        keys.extend(EN_PASSANT_KEYS);
+        keys.push(BLACK_TO_MOVE_KEY);
+
+        let count = keys.len();
+        keys.sort();
+        keys.dedup();
+
+        assert_eq!(keys.len(), count);
+    }
+
+    #[test]
+    fn losing_castling_right_by_rook_capture() {
+        let move_gen = MoveGen::new();
+        let mut board =
+            Board::from_fen("r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 0 1", &move_gen).unwrap();
+        let original = board;
+
+        // Bishop takes the rook on h1, so White can no longer castle kingside
+        let move_data = board.make_move(Move::new(Square::G2, Square::H1)).unwrap();
+
+        assert!(!board.flags.white_kingside());
+        assert_eq!(board.hash, board.zobrist_hash());
+
+        board.unmake_move(move_data).unwrap();
+
+        assert_eq!(board, original);
+        assert_eq!(board.hash, board.zobrist_hash());
+    }
+
+    #[test]
+    fn incremental_hash_matches_from_scratch() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        // King and rook moves, castling, en passant and promotions, two plies deep
+        let mut moves = Vec::new();
+        move_gen.legal_moves(&board, &mut moves);
+
+        for mv in moves {
+            let original = board;
+            let move_data = board.make_move(mv).unwrap();
+
+            assert_eq!(board.hash, board.zobrist_hash(), "{mv}");
+
+            let mut replies = Vec::new();
+            move_gen.legal_moves(&board, &mut replies);
+
+            for reply in replies {
+                let reply_data = board.make_move(reply).unwrap();
+
+                assert_eq!(board.hash, board.zobrist_hash(), "{mv} {reply}");
+
+                board.unmake_move(reply_data).unwrap();
+            }
+
+            board.unmake_move(move_data).unwrap();
+
+            assert_eq!(board, original);
+        }
+    }
+
+    #[test]
+    fn hash_follows_a_game() {
+        let mut board = Board::default();
+        let mut history = Vec::new();
+
+        // Double pushes, en passant, castling on both sides, captures and a promotion
+        for mv in [
+            "e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "b8c6", "g1f3", "c8e6", "f1b5", "d8d6", "e1g1",
+            "e8c8", "f6g7", "d6d2", "g7h8q",
+        ] {
+            history.push(board.make_move(Move::try_from(mv).unwrap()).unwrap());
+
+            assert_eq!(board.hash, board.zobrist_hash(), "{mv}");
+        }
+
+        while let Some(move_data) = history.pop() {
+            board.unmake_move(move_data).unwrap();
+
+            assert_eq!(board.hash, board.zobrist_hash());
+        }
+
+        assert_eq!(board, Board::default());
+    }
+
+    #[test]
+    fn same_position_same_hash() {
+        let move_gen = MoveGen::new();
+        let mut board = Board::default();
+
+        for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.make_move(Move::try_from(mv).unwrap()).unwrap();
+        }
+
+        assert_eq!(board.hash, Board::default().hash);
+        assert_ne!(
+            Board::default().hash,
+            Board::from_fen(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+                &move_gen
+            )
+            .unwrap()
+            .hash
+        );
+    }
+
+    #[test]
+    fn castling_rights_change_hash() {
+        let move_gen = MoveGen::new();
+        let no_black_kingside = Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert_eq!(no_black_kingside.pieces, Board::default().pieces);
+        assert_ne!(no_black_kingside.hash, Board::default().hash);
+    }
+
+    #[test]
+    fn only_capturable_en_passant_changes_hash() {
+        let move_gen = MoveGen::new();
+        let hash = |fen| Board::from_fen(fen, &move_gen).unwrap().hash;
+
+        // The d4 pawn can take on e3
+        assert_ne!(
+            hash("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+            hash("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+        );
+
+        // No black pawn can, so writing e3 is only a FEN convention
+        assert_eq!(
+            hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+            hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+        );
+
+        // The position reached by playing the move matches both
+        let mut board = Board::default();
+        board.make_move(Move::try_from("e2e4").unwrap()).unwrap();
+
+        assert_eq!(
+            board.hash,
+            hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+        );
+
+        // Leaving the unusable en passant square keeps the incremental hash in step
+        let mut board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1", &move_gen).unwrap();
+        board.make_move(Move::try_from("e8d8").unwrap()).unwrap();
+
+        assert_eq!(board.hash, board.zobrist_hash());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::board::{bitboard::Bitboard, square::Square};
+
+/// Squares strictly between two squares on the same rank, file or diagonal, indexed by
+/// both squares. Empty if the squares aren't aligned.
+pub static BETWEEN: [[Bitboard; 64]; 64] = {
+    let mut table = [[Bitboard::EMPTY; 64]; 64];
+
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some((rank_step, file_step)) = direction(a, b) {
+                let mut rank = a as i32 / 8 + rank_step;
+                let mut file = a as i32 % 8 + file_step;
+
+                while rank * 8 + file != b as i32 {
+                    table[a][b].0 |= 1 << (rank * 8 + file);
+                    rank += rank_step;
+                    file += file_step;
+                }
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+
+    table
+};
+
+/// Every square on the rank, file or diagonal through two squares, from one edge of the
+/// board to the other, indexed by both squares. Empty if the squares aren't aligned.
+pub static LINE: [[Bitboard; 64]; 64] = {
+    let mut table = [[Bitboard::EMPTY; 64]; 64];
+
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some((rank_step, file_step)) = direction(a, b) {
+                table[a][b].0 |= 1 << a;
+
+                // Walk from `a` to the edge of the board in both directions
+                let mut sign = -1;
+                while sign <= 1 {
+                    let mut rank = a as i32 / 8 + rank_step * sign;
+                    let mut file = a as i32 % 8 + file_step * sign;
+
+                    while rank >= 0 && rank < 8 && file >= 0 && file < 8 {
+                        table[a][b].0 |= 1 << (rank * 8 + file);
+                        rank += rank_step * sign;
+                        file += file_step * sign;
+                    }
+
+                    sign += 2;
+                }
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+
+    table
+};
+
+/// Squares strictly between `a` and `b`, or an empty bitboard if they aren't on the same
+/// rank, file or diagonal.
+pub fn ray_between(a: Square, b: Square) -> Bitboard {
+    BETWEEN[a as usize][b as usize]
+}
+
+/// Checks whether three squares lie on the same rank, file or diagonal. `a` and `b` must be
+/// different squares to define a line.
+pub fn aligned(a: Square, b: Square, c: Square) -> bool {
+    !(LINE[a as usize][b as usize] & c.bitboard()).is_empty()
+}
+
+/// The rank and file step from `a` towards `b`, if they are different squares on the same
+/// rank, file or diagonal.
+const fn direction(a: usize, b: usize) -> Option<(i32, i32)> {
+    let rank_diff = b as i32 / 8 - a as i32 / 8;
+    let file_diff = b as i32 % 8 - a as i32 % 8;
+
+    if a == b || (rank_diff != 0 && file_diff != 0 && rank_diff.abs() != file_diff.abs()) {
+        return None;
+    }
+
+    Some((rank_diff.signum(), file_diff.signum()))
+}
+
+#[cfg(test)]
+mod lines_tests {
+    use super::*;
+
+    #[test]
+    fn aligned_triples() {
+        let aligned_triples = [
+            // Rank, file, diagonal and anti-diagonal, in any order
+            (Square::A4, Square::H4, Square::D4),
+            (Square::E1, Square::E8, Square::E2),
+            (Square::C3, Square::F6, Square::A1),
+            (Square::H1, Square::A8, Square::D5),
+            (Square::D5, Square::B7, Square::G2),
+        ];
+
+        for (a, b, c) in aligned_triples {
+            assert!(aligned(a, b, c), "{a} {b} {c}");
+            assert!(aligned(b, a, c), "{b} {a} {c}");
+            assert!(aligned(a, c, b), "{a} {c} {b}");
+        }
+
+        let unaligned_triples = [
+            (Square::A1, Square::B3, Square::C5),
+            (Square::E4, Square::E5, Square::D6),
+            (Square::A1, Square::H8, Square::H1),
+            (Square::B1, Square::C3, Square::D5),
+            // Two equal squares don't define a line
+            (Square::D4, Square::D4, Square::D5),
+        ];
+
+        for (a, b, c) in unaligned_triples {
+            assert!(!aligned(a, b, c), "{a} {b} {c}");
+        }
+    }
+
+    #[test]
+    fn ray_between_squares() {
+        assert_eq!(
+            ray_between(Square::A1, Square::D4),
+            Square::B2.bitboard() | Square::C3.bitboard()
+        );
+        assert_eq!(
+            ray_between(Square::H8, Square::H5),
+            Square::H7.bitboard() | Square::H6.bitboard()
+        );
+        assert_eq!(
+            ray_between(Square::B1, Square::F1),
+            Square::C1.bitboard() | Square::D1.bitboard() | Square::E1.bitboard()
+        );
+
+        // Adjacent and unaligned squares have nothing between them
+        assert_eq!(ray_between(Square::E4, Square::F5), Bitboard::EMPTY);
+        assert_eq!(ray_between(Square::A1, Square::B3), Bitboard::EMPTY);
+        assert_eq!(ray_between(Square::C2, Square::C2), Bitboard::EMPTY);
+    }
+}
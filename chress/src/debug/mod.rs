@@ -23,7 +23,88 @@ pub fn perft(board: Board, move_gen: &MoveGen, depth: usize) -> u64 {
     count
 }
 
+/// Like [`perft`], but converts every move to SAN and parses it back, panicking if the
+/// parsed move differs from the generated one. Much slower, but cross-checks the SAN
+/// code against the move generator.
+pub fn perft_san(board: Board, move_gen: &MoveGen, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = Vec::new();
+    move_gen.legal_moves(&board, &mut moves);
+
+    let mut count = 0;
+
+    for mv in moves {
+        let san = board.move_to_san(mv, move_gen);
+
+        match board.parse_san(&san, move_gen) {
+            Ok(parsed) => assert_eq!(parsed, mv, "{san} in {}", board.fen()),
+            Err(err) => panic!("failed to parse {san} ({mv}) in {}: {err}", board.fen()),
+        }
+
+        let mut b = board;
+        b.make_move(mv).unwrap();
+
+        count += perft_san(b, move_gen, depth - 1);
+    }
+
+    count
+}
+
+/// Like [`perft`], but stops counting once `max_nodes` leaf nodes have been found. Returns
+/// the count and whether the tree had more leaf nodes than that.
+pub fn perft_bounded(
+    board: Board,
+    move_gen: &MoveGen,
+    depth: usize,
+    max_nodes: u64,
+) -> (u64, bool) {
+    let mut count = 0;
+    let truncated = perft_bounded_inner(board, move_gen, depth, max_nodes, &mut count);
+
+    (count, truncated)
+}
+
+fn perft_bounded_inner(
+    board: Board,
+    move_gen: &MoveGen,
+    depth: usize,
+    max_nodes: u64,
+    count: &mut u64,
+) -> bool {
+    if depth == 0 {
+        if *count == max_nodes {
+            return true;
+        }
+
+        *count += 1;
+        return false;
+    }
+
+    let mut moves = Vec::new();
+    move_gen.legal_moves(&board, &mut moves);
+
+    for mv in moves {
+        let mut b = board;
+        b.make_move(mv).unwrap();
+
+        if perft_bounded_inner(b, move_gen, depth - 1, max_nodes, count) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Counts the leaf nodes below each legal move, returning the total and the count for each
+/// move. There are no moves to divide at depth 0, so nothing is counted.
 pub fn divide(mut board: Board, move_gen: &MoveGen, depth: usize) -> (u64, Vec<(Move, u64)>) {
+    if depth == 0 {
+        return (0, Vec::new());
+    }
+
     let mut total = 0;
     let mut results = Vec::new();
 
@@ -41,3 +122,89 @@ pub fn divide(mut board: Board, move_gen: &MoveGen, depth: usize) -> (u64, Vec<(
 
     (total, results)
 }
+
+/// Formats the results of [`divide`] exactly like Stockfish's `go perft` output, so that
+/// chress can be compared against any engine using the same format.
+pub fn divide_output(total: u64, results: &[(Move, u64)]) -> String {
+    let mut output = String::new();
+
+    for (mv, count) in results {
+        output.push_str(&format!("{mv}: {count}\n"));
+    }
+
+    output.push_str(&format!("\nNodes searched: {total}\n\n"));
+
+    output
+}
+
+#[cfg(test)]
+mod debug_tests {
+    use super::*;
+
+    #[test]
+    fn divide_output_format() {
+        let move_gen = MoveGen::new();
+        let (total, results) = divide(Board::default(), &move_gen, 2);
+        let output = divide_output(total, &results);
+
+        let mut lines = output.lines();
+
+        for _ in 0..results.len() {
+            let line = lines.next().unwrap();
+            let (mv, count) = line.split_once(": ").unwrap();
+
+            assert_eq!(mv.len(), 4);
+            assert!(Move::try_from(mv).is_ok());
+            assert_eq!(count, "20");
+        }
+
+        assert_eq!(lines.next(), Some(""));
+        assert_eq!(lines.next(), Some("Nodes searched: 400"));
+        assert_eq!(lines.next(), Some(""));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn divide_shallow_depths() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(divide(Board::default(), &move_gen, 0), (0, Vec::new()));
+
+        let (total, results) = divide(Board::default(), &move_gen, 1);
+
+        assert_eq!(total, 20);
+        assert_eq!(results.len(), 20);
+        assert!(results.iter().all(|&(_, count)| count == 1));
+    }
+
+    #[test]
+    fn perft_san_round_trips() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &move_gen,
+        )
+        .unwrap();
+
+        assert_eq!(perft_san(board, &move_gen, 2), 2039);
+    }
+
+    #[test]
+    #[ignore = "slow, run with --ignored"]
+    fn perft_san_start_position() {
+        let move_gen = MoveGen::new();
+
+        assert_eq!(perft_san(Board::default(), &move_gen, 4), 197281);
+    }
+
+    #[test]
+    fn perft_bounded_truncates() {
+        let move_gen = MoveGen::new();
+        let board = Board::default();
+
+        assert_eq!(perft_bounded(board, &move_gen, 3, 10_000), (8902, false));
+        assert_eq!(perft_bounded(board, &move_gen, 3, 8902), (8902, false));
+        assert_eq!(perft_bounded(board, &move_gen, 3, 8901), (8901, true));
+        assert_eq!(perft_bounded(board, &move_gen, 5, 1000), (1000, true));
+    }
+}
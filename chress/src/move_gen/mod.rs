@@ -2,7 +2,7 @@ use crate::{
     board::{
         bitboard::Bitboard,
         color::Color,
-        piece::Piece,
+        piece::{Piece, PromotionPiece},
         r#move::Move,
         sliding_moves::{create_bishop_table, create_rook_table, magic_index},
         square::Square,
@@ -15,6 +15,43 @@ use crate::{
     },
 };
 
+// Unlike sliding pieces, these attacks come from static tables and don't need a `MoveGen`
+
+/// Squares attacked by a knight.
+pub const fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_MOVES[square as usize]
+}
+
+/// Squares attacked by a king.
+pub const fn king_attacks(square: Square) -> Bitboard {
+    KING_MOVES[square as usize]
+}
+
+/// Squares attacked by a pawn of the given color.
+pub const fn pawn_attacks(square: Square, color: Color) -> Bitboard {
+    PAWN_CAPTURES[color as usize][square as usize]
+}
+
+/// Returns the pawns of the given color which can push one square and those which can
+/// push two squares, as `(single, double)`.
+pub fn pawn_pushes(board: &Board, color: Color) -> (Bitboard, Bitboard) {
+    let empty = !board.occupied();
+    let pawns = board.bitboard(Piece::Pawn, color);
+
+    match color {
+        Color::White => {
+            let empty_in_rank_3 = ((empty & Bitboard::RANK_4) >> 8) & empty;
+
+            ((empty >> 8) & pawns, (empty_in_rank_3 >> 8) & pawns)
+        }
+        Color::Black => {
+            let empty_in_rank_6 = ((empty & Bitboard::RANK_5) << 8) & empty;
+
+            ((empty << 8) & pawns, (empty_in_rank_6 << 8) & pawns)
+        }
+    }
+}
+
 // Not deriving Copy because even Cloning this struct would be a bad idea
 #[derive(Debug, Clone)]
 pub struct MoveGen {
@@ -32,26 +69,6 @@ impl MoveGen {
     // * The next few private functions are implemented on MoveGen because I don't really
     // * want to expose them via a public API on the board, but still need to access
     // * them from MoveGen
-    fn white_pawns_able_to_push(board: &Board, empty: Bitboard) -> Bitboard {
-        (empty >> 8) & board.bitboard(Piece::Pawn, Color::White)
-    }
-
-    fn black_pawns_able_to_push(board: &Board, empty: Bitboard) -> Bitboard {
-        (empty << 8) & board.bitboard(Piece::Pawn, Color::Black)
-    }
-
-    fn white_pawns_able_to_double_push(board: &Board, empty: Bitboard) -> Bitboard {
-        const RANK_4: Bitboard = Bitboard(0x00000000FF000000);
-        let empty_in_rank_3 = ((empty & RANK_4) >> 8) & empty;
-        Self::white_pawns_able_to_push(board, empty_in_rank_3)
-    }
-
-    fn black_pawns_able_to_double_push(board: &Board, empty: Bitboard) -> Bitboard {
-        const RANK_5: Bitboard = Bitboard(0x000000FF00000000);
-        let empty_in_rank_6 = ((empty & RANK_5) << 8) & empty;
-        Self::black_pawns_able_to_push(board, empty_in_rank_6)
-    }
-
     /// Used with sliding pieces
     fn append_moves_getter(
         &self,
@@ -134,28 +151,54 @@ impl MoveGen {
         attacks & !friendly_pieces
     }
 
+    /// Returns every piece of either color attacking a square, treating only the pieces
+    /// in `occupied` as blockers for sliding pieces.
+    pub fn attackers_to(&self, board: &Board, square: Square, occupied: Bitboard) -> Bitboard {
+        let pawns = (pawn_attacks(square, Color::Black)
+            & board.bitboard(Piece::Pawn, Color::White))
+            | (pawn_attacks(square, Color::White) & board.bitboard(Piece::Pawn, Color::Black));
+
+        let knights = board.bitboard(Piece::Knight, Color::White)
+            | board.bitboard(Piece::Knight, Color::Black);
+        let kings =
+            board.bitboard(Piece::King, Color::White) | board.bitboard(Piece::King, Color::Black);
+        let queens =
+            board.bitboard(Piece::Queen, Color::White) | board.bitboard(Piece::Queen, Color::Black);
+        let rooks = board.bitboard(Piece::Rook, Color::White)
+            | board.bitboard(Piece::Rook, Color::Black)
+            | queens;
+        let bishops = board.bitboard(Piece::Bishop, Color::White)
+            | board.bitboard(Piece::Bishop, Color::Black)
+            | queens;
+
+        pawns
+            | (knight_attacks(square) & knights)
+            | (king_attacks(square) & kings)
+            | (self.rook_attacks(square, occupied) & rooks)
+            | (self.bishop_attacks(square, occupied) & bishops)
+    }
+
     // ? This function has been benchmarked against the branchless version, which was slower.
     /// Checks if a square is seen by pieces of a certain color for the
     /// purpose of legal move generation
     pub fn square_attacked_by(&self, board: &Board, square: Square, attacker_color: Color) -> bool {
-        let pawn_attackers = PAWN_CAPTURES[attacker_color.inverse() as usize][square as usize]
+        let pawn_attackers = pawn_attacks(square, attacker_color.inverse())
             & board.bitboard(Piece::Pawn, attacker_color);
 
         if !pawn_attackers.is_empty() {
             return true;
         }
 
-        let king_attacks =
-            KING_MOVES[square as usize] & board.bitboard(Piece::King, attacker_color);
+        let king_attackers = king_attacks(square) & board.bitboard(Piece::King, attacker_color);
 
-        if !king_attacks.is_empty() {
+        if !king_attackers.is_empty() {
             return true;
         }
 
-        let knight_attacks =
-            KNIGHT_MOVES[square as usize] & board.bitboard(Piece::Knight, attacker_color);
+        let knight_attackers =
+            knight_attacks(square) & board.bitboard(Piece::Knight, attacker_color);
 
-        if !knight_attacks.is_empty() {
+        if !knight_attackers.is_empty() {
             return true;
         }
 
@@ -178,6 +221,37 @@ impl MoveGen {
         false
     }
 
+    /// Counts the pseudolegal captures available to the side to move, without generating
+    /// them. Capture promotions count once, and en passant isn't counted.
+    pub fn capture_count(&self, board: &Board) -> usize {
+        let color = board.active_color;
+        let enemy_pieces = board.enemy_pieces();
+        let occupied = board.occupied();
+
+        let mut count = 0;
+
+        for piece in Piece::ALL {
+            let mut pieces = board.bitboard(piece, color);
+
+            for _ in 0..pieces.0.count_ones() {
+                let from = Square::ALL[pieces.pop_lsb() as usize];
+
+                let attacks = match piece {
+                    Piece::Knight => knight_attacks(from),
+                    Piece::Bishop => self.bishop_attacks(from, occupied),
+                    Piece::Rook => self.rook_attacks(from, occupied),
+                    Piece::Queen => self.queen_attacks(from, occupied),
+                    Piece::King => king_attacks(from),
+                    Piece::Pawn => pawn_attacks(from, color),
+                };
+
+                count += (attacks & enemy_pieces).0.count_ones() as usize;
+            }
+        }
+
+        count
+    }
+
     /// Get all pseudolegal moves
     pub fn pseudolegal_moves(&self, board: &Board, moves: &mut Vec<Move>) -> usize {
         let color = board.active_color;
@@ -186,22 +260,7 @@ impl MoveGen {
         let friendly_pieces = board.friendly_pieces();
         let enemy_pieces = board.enemy_pieces();
 
-        let all_pieces = friendly_pieces | enemy_pieces;
-        let empty_squares = !all_pieces;
-
-        // Pawn move data
-        let pawn_data = [
-            (
-                Self::white_pawns_able_to_push(board, empty_squares),
-                Self::white_pawns_able_to_double_push(board, empty_squares),
-            ),
-            (
-                Self::black_pawns_able_to_push(board, empty_squares),
-                Self::black_pawns_able_to_double_push(board, empty_squares),
-            ),
-        ];
-
-        let (mut single_push_froms, mut double_push_froms) = pawn_data[color as usize];
+        let (mut single_push_froms, mut double_push_froms) = pawn_pushes(board, color);
 
         let mut pawns = board.bitboard(Piece::Pawn, color);
 
@@ -209,16 +268,21 @@ impl MoveGen {
         for _ in 0..single_push_froms.0.count_ones() {
             let from = Square::ALL[single_push_froms.pop_lsb() as usize];
 
-            let to_index = from as i8 + (8 * color.direction());
-            let to = Square::try_from(to_index as usize).unwrap();
+            // Malformed boards may have pawns on the last rank, which have nowhere to go
+            let Some(to) = (from as usize)
+                .checked_add_signed(8 * color.direction() as isize)
+                .and_then(|index| Square::try_from(index).ok())
+            else {
+                continue;
+            };
 
             // Promotion
-            if to.rank() % 7 == 0 {
+            if to.rank().is_multiple_of(7) {
                 // ? Not sure if this branch can actually be removed
-                moves.push(Move::new_with_promotion(from, to, Piece::Knight));
-                moves.push(Move::new_with_promotion(from, to, Piece::Bishop));
-                moves.push(Move::new_with_promotion(from, to, Piece::Rook));
-                moves.push(Move::new_with_promotion(from, to, Piece::Queen));
+                moves.push(Move::new_with_promotion(from, to, PromotionPiece::Knight));
+                moves.push(Move::new_with_promotion(from, to, PromotionPiece::Bishop));
+                moves.push(Move::new_with_promotion(from, to, PromotionPiece::Rook));
+                moves.push(Move::new_with_promotion(from, to, PromotionPiece::Queen));
             } else {
                 moves.push(Move::new(from, to));
             }
@@ -228,8 +292,12 @@ impl MoveGen {
         for _ in 0..double_push_froms.0.count_ones() {
             let from = Square::ALL[double_push_froms.pop_lsb() as usize];
 
-            let to_index = from as isize + (16 * color.direction()) as isize;
-            let to = Square::ALL[to_index as usize];
+            let Some(to) = (from as usize)
+                .checked_add_signed(16 * color.direction() as isize)
+                .and_then(|index| Square::try_from(index).ok())
+            else {
+                continue;
+            };
 
             moves.push(Move::new(from, to));
         }
@@ -245,11 +313,11 @@ impl MoveGen {
                 let to = Square::ALL[captures.pop_lsb() as usize];
 
                 // ? Not sure if this branch can actually be removed
-                if to.rank() % 7 == 0 {
-                    moves.push(Move::new_with_promotion(from, to, Piece::Knight));
-                    moves.push(Move::new_with_promotion(from, to, Piece::Bishop));
-                    moves.push(Move::new_with_promotion(from, to, Piece::Rook));
-                    moves.push(Move::new_with_promotion(from, to, Piece::Queen));
+                if to.rank().is_multiple_of(7) {
+                    moves.push(Move::new_with_promotion(from, to, PromotionPiece::Knight));
+                    moves.push(Move::new_with_promotion(from, to, PromotionPiece::Bishop));
+                    moves.push(Move::new_with_promotion(from, to, PromotionPiece::Rook));
+                    moves.push(Move::new_with_promotion(from, to, PromotionPiece::Queen));
                 } else {
                     moves.push(Move::new(from, to));
                 }
@@ -278,17 +346,15 @@ impl MoveGen {
         }
 
         // King moves
-        let king_index = board.bitboard(Piece::King, color).0.trailing_zeros() as usize;
-        let king_square = Square::ALL[king_index];
-
-        // let mut targets = self.pseudo_king_moves(board, king_square);
-        let mut targets = KING_MOVES[king_square as usize] & !friendly_pieces;
-        targets.append_moves_from(moves, king_square);
+        // Uses the bitboard rather than the king's square so that malformed boards
+        // without a king don't panic
+        let kings = board.bitboard(Piece::King, color);
+        self.append_moves_table(moves, kings, friendly_pieces, &KING_MOVES);
 
         // Castling
         // Check if king is on start square and not in check
         let king_start_square = KING_STARTING_SQUARES[color as usize];
-        let on_start_square = king_square == king_start_square;
+        let on_start_square = !(kings & king_start_square.bitboard()).is_empty();
         let in_check = self.square_attacked_by(board, king_start_square, attacker_color);
 
         if on_start_square && !in_check {
@@ -311,9 +377,8 @@ impl MoveGen {
                     continue;
                 }
 
-                // Check if castling through/out of check
-                // Don't need to check if castling into check as that is checked
-                // in legal_moves already (would be redundant)
+                // Check if castling through or into check. Being in check was ruled out
+                // above, and the checkable squares include the king's destination
                 let mut checkables = CASTLING_CHECKABLES[color as usize][i];
 
                 for _ in 0..checkables.0.count_ones() {
@@ -366,23 +431,25 @@ impl MoveGen {
     }
 
     /// Generate all legal moves at the current position
+    ///
+    /// Moves are in the same order as `pseudolegal_moves` generates them, with illegal
+    /// moves removed: pawn pushes, double pushes, captures and en passant, then king moves,
+    /// castling, knights, rooks, bishops and queens. Within each group, moves are ordered
+    /// by their from square and then their to square.
     pub fn legal_moves(&self, board: &Board, moves: &mut Vec<Move>) -> usize {
-        let mut len = self.pseudolegal_moves(board, moves);
+        self.pseudolegal_moves(board, moves);
 
-        let mut i = 0;
+        moves.retain(|&mv| self.is_legal_move(*board, mv));
 
-        while i < len {
-            let mv = moves[i];
-
-            if !self.is_legal_move(*board, mv) {
-                moves.swap_remove(i);
-                len -= 1;
-            } else {
-                i += 1;
-            }
-        }
+        moves.len()
+    }
 
-        len
+    /// Like [`MoveGen::legal_moves`], but allocates and returns a new `Vec`. Prefer
+    /// `legal_moves` with a reused buffer in hot loops.
+    pub fn legal_moves_vec(&self, board: &Board) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.legal_moves(board, &mut moves);
+        moves
     }
 }
 
@@ -391,3 +458,319 @@ impl Default for MoveGen {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod move_gen_tests {
+    use super::*;
+
+    #[test]
+    fn en_passant_exposing_king_on_rank() {
+        let move_gen = MoveGen::new();
+        let en_passant = Move::new(Square::C5, Square::D6);
+
+        // Both pawns leave the fifth rank, so capturing en passant exposes the king to the
+        // rook on h5
+        let board = Board::from_fen("8/8/8/K1Pp3r/8/8/8/7k w - d6 0 1", &move_gen).unwrap();
+
+        let mut pseudolegal = Vec::new();
+        move_gen.pseudolegal_moves(&board, &mut pseudolegal);
+
+        assert!(pseudolegal.contains(&en_passant));
+        assert!(!move_gen.is_legal_move(board, en_passant));
+        assert!(!move_gen.legal_moves_vec(&board).contains(&en_passant));
+
+        // Without the rook the capture is legal
+        let board = Board::from_fen("8/8/8/K1Pp4/8/8/8/7k w - d6 0 1", &move_gen).unwrap();
+
+        assert!(move_gen.legal_moves_vec(&board).contains(&en_passant));
+
+        // The same for black, with the rook on the other side of the king
+        let board = Board::from_fen("7K/8/8/8/R2pP2k/8/8/8 b - e3 0 1", &move_gen).unwrap();
+
+        let en_passant = Move::new(Square::D4, Square::E3);
+
+        let mut pseudolegal = Vec::new();
+        move_gen.pseudolegal_moves(&board, &mut pseudolegal);
+
+        assert!(pseudolegal.contains(&en_passant));
+        assert!(!move_gen.legal_moves_vec(&board).contains(&en_passant));
+    }
+
+    #[test]
+    fn legal_moves_vec_matches_legal_moves() {
+        let move_gen = MoveGen::new();
+
+        for fen in [
+            crate::board::START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            let mut moves = Vec::new();
+            move_gen.legal_moves(&board, &mut moves);
+
+            assert_eq!(move_gen.legal_moves_vec(&board), moves, "{fen}");
+        }
+    }
+
+    /// A bitboard of the given squares
+    fn squares(squares: &[Square]) -> Bitboard {
+        squares
+            .iter()
+            .fold(Bitboard::EMPTY, |mask, square| mask | square.bitboard())
+    }
+
+    #[test]
+    fn pawn_pushes_startpos() {
+        let board = Board::default();
+
+        let (single, double) = pawn_pushes(&board, Color::White);
+        assert_eq!(single, Bitboard(0x0000_0000_0000_FF00));
+        assert_eq!(double, Bitboard(0x0000_0000_0000_FF00));
+
+        let (single, double) = pawn_pushes(&board, Color::Black);
+        assert_eq!(single, Bitboard(0x00FF_0000_0000_0000));
+        assert_eq!(double, Bitboard(0x00FF_0000_0000_0000));
+    }
+
+    #[test]
+    fn pawn_pushes_blocked() {
+        let move_gen = MoveGen::new();
+        let board =
+            Board::from_fen("4k3/p1p5/2N5/p7/P7/1n6/1P1P4/4K3 w - - 0 1", &move_gen).unwrap();
+
+        // b2 is blocked, d2 can push once or twice, a4 is blocked
+        let (single, double) = pawn_pushes(&board, Color::White);
+        assert_eq!(single, Square::D2.bitboard());
+        assert_eq!(double, Square::D2.bitboard());
+
+        // c7 is blocked, a5 is blocked, a7 can only push once
+        let (single, double) = pawn_pushes(&board, Color::Black);
+        assert_eq!(single, Square::A7.bitboard());
+        assert_eq!(double, Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn malformed_board_does_not_panic() {
+        let move_gen = MoveGen::new();
+
+        // Pawns on the last rank and no kings
+        let mut board = Board {
+            pieces: [Bitboard::EMPTY; 12],
+            ..Board::default()
+        };
+        board.pieces[Piece::Pawn as usize] = Square::A8.bitboard() | Square::B7.bitboard();
+        board.pieces[Piece::Pawn as usize + 6] = Square::H1.bitboard() | Square::G2.bitboard();
+
+        let mut moves = Vec::new();
+        move_gen.pseudolegal_moves(&board, &mut moves);
+
+        assert!(moves.iter().all(|mv| mv.from() == Square::B7));
+        assert_eq!(moves.len(), 4);
+
+        board.flip_color();
+        moves.clear();
+        move_gen.pseudolegal_moves(&board, &mut moves);
+
+        assert!(moves.iter().all(|mv| mv.from() == Square::G2));
+        assert_eq!(moves.len(), 4);
+    }
+
+    #[test]
+    fn legal_moves_keep_generation_order() {
+        let move_gen = MoveGen::new();
+
+        // The knight on d2 is pinned, so its moves are removed
+        let board = Board::from_fen("4k3/8/8/b7/8/8/3N4/4K1N1 w - - 0 1", &move_gen).unwrap();
+
+        let mut pseudolegal = Vec::new();
+        move_gen.pseudolegal_moves(&board, &mut pseudolegal);
+
+        let mut legal = Vec::new();
+        move_gen.legal_moves(&board, &mut legal);
+
+        pseudolegal.retain(|&mv| move_gen.is_legal_move(board, mv));
+
+        assert_eq!(legal, pseudolegal);
+
+        let legal = legal.iter().map(Move::to_string).collect::<Vec<String>>();
+
+        assert_eq!(
+            legal,
+            ["e1d1", "e1f1", "e1e2", "e1f2", "g1e2", "g1f3", "g1h3"]
+        );
+    }
+
+    #[test]
+    fn capture_count_matches_generated_captures() {
+        let move_gen = MoveGen::new();
+
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            crate::board::START_FEN,
+        ];
+
+        for fen in fens {
+            let board = Board::from_fen(fen, &move_gen).unwrap();
+
+            let mut moves = Vec::new();
+            move_gen.pseudolegal_moves(&board, &mut moves);
+
+            // Capture promotions are generated once per promotion piece
+            let mut captures = moves
+                .iter()
+                .filter(|mv| !(board.enemy_pieces() & mv.to().bitboard()).is_empty())
+                .map(|mv| (mv.from(), mv.to()))
+                .collect::<Vec<(Square, Square)>>();
+            captures.dedup();
+
+            assert_eq!(move_gen.capture_count(&board), captures.len(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn knight_attacks_corner_edge_centre() {
+        use Square::*;
+
+        assert_eq!(knight_attacks(A1), squares(&[B3, C2]));
+        assert_eq!(knight_attacks(H8), squares(&[G6, F7]));
+        assert_eq!(knight_attacks(A4), squares(&[B6, C5, C3, B2]));
+        assert_eq!(knight_attacks(G2), squares(&[E1, E3, F4, H4]));
+        assert_eq!(
+            knight_attacks(D4),
+            squares(&[C6, E6, F5, F3, E2, C2, B3, B5])
+        );
+    }
+
+    #[test]
+    fn king_attacks_corner_edge_centre() {
+        use Square::*;
+
+        assert_eq!(king_attacks(A1), squares(&[A2, B2, B1]));
+        assert_eq!(king_attacks(H8), squares(&[G8, G7, H7]));
+        assert_eq!(king_attacks(E1), squares(&[D1, F1, D2, E2, F2]));
+        assert_eq!(king_attacks(D4), squares(&[C3, C4, C5, D3, D5, E3, E4, E5]));
+    }
+
+    #[test]
+    fn pawn_attacks_corner_edge_centre() {
+        use Square::*;
+
+        assert_eq!(pawn_attacks(A2, Color::White), squares(&[B3]));
+        assert_eq!(pawn_attacks(H7, Color::White), squares(&[G8]));
+        assert_eq!(pawn_attacks(E4, Color::White), squares(&[D5, F5]));
+        assert_eq!(pawn_attacks(H8, Color::White), Bitboard::EMPTY);
+
+        assert_eq!(pawn_attacks(A7, Color::Black), squares(&[B6]));
+        assert_eq!(pawn_attacks(H2, Color::Black), squares(&[G1]));
+        assert_eq!(pawn_attacks(E5, Color::Black), squares(&[D4, F4]));
+        assert_eq!(pawn_attacks(A1, Color::Black), Bitboard::EMPTY);
+    }
+
+    /// The legal castling moves in a position, as strings
+    fn castling_moves(fen: &str) -> Vec<String> {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen(fen, &move_gen).unwrap();
+
+        let mut moves = Vec::new();
+        move_gen.legal_moves(&board, &mut moves);
+
+        let mut castling = moves
+            .into_iter()
+            .filter(|mv| {
+                board.piece_at(mv.from()) == Some(Piece::King)
+                    && mv.from().file().abs_diff(mv.to().file()) == 2
+            })
+            .map(|mv| mv.to_string())
+            .collect::<Vec<String>>();
+
+        castling.sort();
+        castling
+    }
+
+    #[test]
+    fn castling_legal() {
+        assert_eq!(
+            castling_moves("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"),
+            ["e1c1", "e1g1"]
+        );
+        assert_eq!(
+            castling_moves("r3k2r/8/8/8/8/8/8/4K3 b kq - 0 1"),
+            ["e8c8", "e8g8"]
+        );
+    }
+
+    #[test]
+    fn castling_out_of_check() {
+        assert!(castling_moves("4k3/8/8/4r3/8/8/8/R3K2R w KQ - 0 1").is_empty());
+        assert!(castling_moves("r3k2r/8/8/1B6/8/8/8/4K3 b kq - 0 1").is_empty());
+    }
+
+    #[test]
+    fn castling_through_check() {
+        // f1 and d1 are the squares the king passes through
+        assert_eq!(
+            castling_moves("4k3/8/8/5r2/8/8/8/R3K2R w KQ - 0 1"),
+            ["e1c1"]
+        );
+        assert_eq!(
+            castling_moves("4k3/8/8/3r4/8/8/8/R3K2R w KQ - 0 1"),
+            ["e1g1"]
+        );
+    }
+
+    #[test]
+    fn castling_into_check() {
+        assert_eq!(
+            castling_moves("4k3/8/8/6r1/8/8/8/R3K2R w KQ - 0 1"),
+            ["e1c1"]
+        );
+        assert_eq!(
+            castling_moves("r3k2r/8/8/8/8/8/8/2R1K3 b kq - 0 1"),
+            ["e8g8"]
+        );
+    }
+
+    #[test]
+    fn queenside_castling_with_b_file_attacked() {
+        // The rook passes over b1 or b8 but the king doesn't, so it may be attacked
+        assert_eq!(
+            castling_moves("4k3/8/8/1r6/8/8/8/R3K2R w KQ - 0 1"),
+            ["e1c1", "e1g1"]
+        );
+        assert_eq!(
+            castling_moves("r3k2r/8/8/8/1R6/8/8/4K3 b kq - 0 1"),
+            ["e8c8", "e8g8"]
+        );
+    }
+
+    #[test]
+    fn promotions_by_push_and_capture() {
+        let move_gen = MoveGen::new();
+        let board = Board::from_fen("2r1r3/3P2k1/8/8/8/8/8/K7 w - - 0 1", &move_gen).unwrap();
+
+        let mut moves = Vec::new();
+        move_gen.pseudolegal_moves(&board, &mut moves);
+
+        let promotions = moves
+            .iter()
+            .filter(|mv| mv.promotion().is_some())
+            .collect::<Vec<&Move>>();
+
+        assert_eq!(promotions.len(), 12);
+
+        for to in [Square::C8, Square::D8, Square::E8] {
+            let mut pieces = promotions
+                .iter()
+                .filter(|mv| mv.from() == Square::D7 && mv.to() == to)
+                .map(|mv| mv.promotion().unwrap())
+                .collect::<Vec<PromotionPiece>>();
+            pieces.sort_by_key(|&piece| piece as u8);
+
+            assert_eq!(pieces, PromotionPiece::ALL, "{to}");
+        }
+    }
+}